@@ -29,7 +29,7 @@ impl Testbed for Example {
             println!("{:?}", col);
         }
 
-        DynamicBody::create(newton, &b, transform(0.0, 0.0, 0.1), None).into_handle(newton);
+        DynamicBody::create(newton, &b, transform(0.0, 0.0, 0.1), None::<&str>).into_handle(newton);
 
         let body =
             DynamicBody::create(newton, &compound, transform(0.0, 2.0, 0.0), Some("compound"));
@@ -40,7 +40,7 @@ impl Testbed for Example {
 
         // ground
         let ground = Cuboid::create(newton, 8.0, 0.5, 8.0, None);
-        let ground = DynamicBody::create(newton, &ground, transform(0.0, 0.0, 0.0), None);
+        let ground = DynamicBody::create(newton, &ground, transform(0.0, 0.0, 0.0), None::<&str>);
         ground.into_handle(newton);
 
         Self
@@ -22,7 +22,7 @@ impl Testbed for Joints {
             let parent = newton.storage().body(last_body).unwrap();
 
             let ball = DynamicBody::create(newton, &sphere, pos(offset, offset, 0.0), Some("ball"));
-            let joint = Ball::create(newton, [pivot, pivot, 0.0], &ball, Some(&parent), None);
+            let joint = Ball::create(newton, [pivot, pivot, 0.0], &ball, Some(&parent), None::<&str>);
 
             joint.set_cone_limits([-1.0, 0.0, 0.0], 0.0, 0.0);
             joint.set_destroy_callback(|| println!("Destroy joint"));
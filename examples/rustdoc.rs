@@ -10,10 +10,10 @@ fn main() {
     let plane = Cuboid::create(&world, 16.0, 0.1, 16.0, None);
 
     // create plane
-    DynamicBody::create(&world, &plane, pos(0.0, 0.0, 0.0), None).into_handle(&world);
+    DynamicBody::create(&world, &plane, pos(0.0, 0.0, 0.0), None::<&str>).into_handle(&world);
 
     /// Create free-fall body
-    let body = DynamicBody::create(&world, &sphere, pos(0.0, 8.0, 0.0), None);
+    let body = DynamicBody::create(&world, &sphere, pos(0.0, 8.0, 0.0), None::<&str>);
     body.set_mass(1.0, &sphere);
     body.set_force_and_torque_callback(|b, _, _| {
             let (mass, _) = b.mass();
@@ -15,9 +15,9 @@ impl Testbed for TreeDemo {
             build.optimize();
         }
 
-        DynamicBody::create(newton, &floor, trans(0.0, 0.0, 0.0), None).into_handle(newton);
+        DynamicBody::create(newton, &floor, trans(0.0, 0.0, 0.0), None::<&str>).into_handle(newton);
 
-        let body = DynamicBody::create(newton, &tree, trans(0.0, 4.0, 0.0), None);
+        let body = DynamicBody::create(newton, &tree, trans(0.0, 4.0, 0.0), None::<&str>);
         body.set_mass(1.0, &tree);
         body.set_force_and_torque_callback(|b, _, _| b.set_force([0.0, -9.8, 0.0]));
         body.into_handle(newton);
@@ -0,0 +1,224 @@
+use std::time::Duration;
+
+use crate::math::{add, lerp, mul, quat_conjugate, quat_mul, quat_slerp, quat_to_mat,
+                  quat_to_rotation_vector, sub, Mat4, Quat, Vec3};
+
+use super::NewtonBody;
+
+/// Interpolation between keyframes.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Interpolation {
+    Linear,
+    /// Catmull-Rom spline through the keyframe positions. Rotations are
+    /// interpolated spherically in both modes.
+    Spline,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Keyframe {
+    /// Time from the start of the path, in seconds.
+    pub time: f32,
+    pub position: Vec3,
+    /// Unit quaternion, laid out as `[w, x, y, z]`.
+    pub rotation: Quat,
+}
+
+/// Keyframed path for kinematic bodies (moving platforms, elevators, doors...).
+#[derive(Debug, Clone, PartialEq)]
+pub struct KinematicPath {
+    keyframes: Vec<Keyframe>,
+    interpolation: Interpolation,
+    looping: bool,
+}
+
+impl KinematicPath {
+    pub fn new(interpolation: Interpolation) -> Self {
+        Self { keyframes: Vec::new(), interpolation, looping: false }
+    }
+
+    /// Adds a keyframe. Keyframes don't need to be added in order.
+    pub fn keyframe(mut self, time: Duration, position: Vec3, rotation: Quat) -> Self {
+        let time = time.as_secs_f32();
+        let idx = self.keyframes.iter().take_while(|k| k.time <= time).count();
+        self.keyframes.insert(idx, Keyframe { time, position, rotation });
+        self
+    }
+
+    /// Restarts the path once the last keyframe is reached. For a seamless loop,
+    /// the last keyframe should match the first one.
+    pub fn looping(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    pub fn keyframes(&self) -> &[Keyframe] {
+        &self.keyframes
+    }
+
+    /// Time of the last keyframe.
+    pub fn duration(&self) -> Duration {
+        let secs = self.keyframes.last().map(|k| k.time).unwrap_or(0.0);
+        Duration::from_secs_f32(secs)
+    }
+
+    fn wrap(&self, time: f32) -> f32 {
+        let duration = self.keyframes.last().map(|k| k.time).unwrap_or(0.0);
+        if self.looping && duration > 0.0 {
+            time % duration
+        } else {
+            time.min(duration)
+        }
+    }
+
+    /// Returns the position & rotation at the given time.
+    ///
+    /// # Panics
+    /// Panics if the path has no keyframes.
+    pub fn sample(&self, time: Duration) -> (Vec3, Quat) {
+        self.sample_secs(time.as_secs_f32())
+    }
+
+    fn sample_secs(&self, time: f32) -> (Vec3, Quat) {
+        let keys = &self.keyframes;
+        assert!(!keys.is_empty(), "Path has no keyframes");
+
+        let time = self.wrap(time);
+        let next = keys.iter().take_while(|k| k.time <= time).count();
+        if next == 0 {
+            return (keys[0].position, keys[0].rotation);
+        }
+        if next == keys.len() {
+            let last = keys[next - 1];
+            return (last.position, last.rotation);
+        }
+
+        let (k1, k2) = (keys[next - 1], keys[next]);
+        let t = (time - k1.time) / (k2.time - k1.time);
+        let rotation = quat_slerp(k1.rotation, k2.rotation, t);
+        let position = match self.interpolation {
+            Interpolation::Linear => lerp(k1.position, k2.position, t),
+            Interpolation::Spline => {
+                let p0 = keys[next.saturating_sub(2)].position;
+                let p3 = keys[(next + 1).min(keys.len() - 1)].position;
+                catmull_rom(p0, k1.position, k2.position, p3, t)
+            }
+        };
+        (position, rotation)
+    }
+}
+
+fn catmull_rom(p0: Vec3, p1: Vec3, p2: Vec3, p3: Vec3, t: f32) -> Vec3 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    let a = mul(p1, 2.0);
+    let b = mul(sub(p2, p0), t);
+    let c = mul(add(sub(mul(p0, 2.0), mul(p1, 5.0)), sub(mul(p2, 4.0), p3)), t2);
+    let d = mul(add(sub(mul(p1, 3.0), p0), sub(p3, mul(p2, 3.0))), t3);
+    mul(add(add(a, b), add(c, d)), 0.5)
+}
+
+/// Drives a kinematic body along a `KinematicPath`.
+///
+/// Call `step` before every world update, with the same timestep. On top of setting the
+/// matrix, the driver sets the linear & angular velocities that take the body to the next
+/// keyframe sample, so dynamic bodies resting on it are carried along by friction.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use newton::prelude::*;
+/// use newton::body::animation::{Interpolation, KinematicAnimation, KinematicPath};
+/// use newton::{Cuboid, KinematicBody, Newton};
+///
+/// let mut newton = Newton::create();
+/// let cuboid = Cuboid::create(&newton, 4.0, 0.2, 4.0, None);
+/// let platform = KinematicBody::builder(&newton, &cuboid).build();
+///
+/// // elevator
+/// let identity = [1.0, 0.0, 0.0, 0.0];
+/// let path = KinematicPath::new(Interpolation::Linear)
+///     .keyframe(Duration::from_secs(0), [0.0, 0.0, 0.0], identity)
+///     .keyframe(Duration::from_secs(2), [0.0, 5.0, 0.0], identity)
+///     .keyframe(Duration::from_secs(4), [0.0, 0.0, 0.0], identity)
+///     .looping(true);
+///
+/// let mut animation = KinematicAnimation::new(path);
+///
+/// let step = Duration::new(0, 1_000_000_000 / 60);
+/// for _ in 0..60 {
+///     animation.step(&platform, step);
+///     newton.update(step);
+/// }
+///
+/// assert!(platform.velocity()[1] > 0.0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct KinematicAnimation {
+    path: KinematicPath,
+    time: f32,
+    playing: bool,
+}
+
+impl KinematicAnimation {
+    pub fn new(path: KinematicPath) -> Self {
+        Self { path, time: 0.0, playing: true }
+    }
+
+    pub fn path(&self) -> &KinematicPath {
+        &self.path
+    }
+
+    /// Time elapsed since the start of the path.
+    pub fn time(&self) -> Duration {
+        Duration::from_secs_f32(self.time)
+    }
+
+    /// Jumps to the given time. The body is moved on the next step.
+    pub fn seek(&mut self, time: Duration) {
+        self.time = time.as_secs_f32();
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// Pauses or resumes the animation. Paused bodies stand still.
+    pub fn set_playing(&mut self, playing: bool) {
+        self.playing = playing;
+    }
+
+    /// True once a non-looping path has reached its last keyframe.
+    pub fn is_finished(&self) -> bool {
+        !self.path.looping && self.time >= self.path.duration().as_secs_f32()
+    }
+
+    /// Moves the body to the current sample of the path and sets the velocities that take
+    /// it to the next one after `step`. Does nothing if the path has no keyframes.
+    pub fn step<B: NewtonBody>(&mut self, body: &B, step: Duration) {
+        if self.path.keyframes.is_empty() {
+            return;
+        }
+
+        let dt = step.as_secs_f32();
+        let (position, rotation) = self.path.sample_secs(self.time);
+        let mut matrix: Mat4 = quat_to_mat(rotation);
+        matrix[3] = [position[0], position[1], position[2], 1.0];
+        body.set_matrix(matrix);
+
+        if !self.playing || dt <= 0.0 {
+            body.set_velocity([0.0, 0.0, 0.0]);
+            body.set_omega([0.0, 0.0, 0.0]);
+            return;
+        }
+
+        let time = self.time + dt;
+        let (next_position, next_rotation) = self.path.sample_secs(time);
+        let delta = quat_mul(next_rotation, quat_conjugate(rotation));
+
+        body.set_velocity(mul(sub(next_position, position), 1.0 / dt));
+        body.set_omega(mul(quat_to_rotation_vector(delta), 1.0 / dt));
+
+        // keep the clock bounded on looping paths, to preserve precision
+        self.time = if self.path.looping { self.path.wrap(time) } else { time };
+    }
+}
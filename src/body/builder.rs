@@ -0,0 +1,285 @@
+use std::borrow::Cow;
+use std::time::Duration;
+
+use crate::collision::NewtonCollision;
+use crate::handle::{BodyHandle, IntoHandle};
+use crate::math::{quat_to_mat, set_euler_angle, Mat4, Quat, Vec3};
+use crate::newton::Newton;
+
+use super::{Body, NewtonBody, SleepState};
+
+type ForceAndTorque = Box<dyn FnMut(Body, Duration, usize) + Send>;
+type Transform = Box<dyn FnMut(Body, Mat4, usize) + Send>;
+type Destructor = Box<dyn FnMut(Body) + Send>;
+
+#[derive(Debug, Clone, Copy)]
+enum Mass {
+    Mass(f32),
+    Density(f32),
+}
+
+/// Body configuration, returned by `DynamicBody::builder` & `KinematicBody::builder`.
+///
+/// Properties that are not set keep the defaults Newton gives to new bodies.
+///
+/// ```
+/// use newton::prelude::*;
+/// use newton::{Cuboid, DynamicBody, Newton};
+///
+/// let newton = Newton::config().gravity([0.0, -9.8, 0.0]).build();
+/// let cuboid = Cuboid::create(&newton, 1.0, 1.0, 1.0, None);
+///
+/// let handle = DynamicBody::builder(&newton, &cuboid)
+///     .position([0.0, 4.0, 0.0])
+///     .mass(2.0)
+///     .linear_damping(0.2)
+///     .velocity([1.0, 0.0, 0.0])
+///     .name("crate")
+///     .into_handle();
+///
+/// let body = newton.storage().body(handle).unwrap();
+/// assert_eq!(Some("crate"), body.name().as_deref());
+/// assert_eq!(2.0, body.mass().0);
+/// ```
+pub struct BodyBuilder<'a, 'c, B, C> {
+    newton: &'a Newton,
+    collision: &'c C,
+    create: fn(&'a Newton, &'c C, Mat4) -> B,
+
+    matrix: Mat4,
+    name: Option<Cow<'static, str>>,
+    mass: Option<Mass>,
+    inertia: Option<Vec3>,
+    centre_of_mass: Option<Vec3>,
+    linear_damping: Option<f32>,
+    angular_damping: Option<Vec3>,
+    continuous: Option<bool>,
+    material_group: Option<i32>,
+    sleep_state: Option<SleepState>,
+    auto_sleep: Option<bool>,
+    velocity: Option<Vec3>,
+    omega: Option<Vec3>,
+    force_and_torque: Option<ForceAndTorque>,
+    transform: Option<Transform>,
+    destructor: Option<Destructor>,
+}
+
+impl<'a, 'c, B, C> BodyBuilder<'a, 'c, B, C>
+    where B: NewtonBody + IntoHandle<Handle = BodyHandle>,
+          C: NewtonCollision
+{
+    pub(crate) fn new(newton: &'a Newton,
+                      collision: &'c C,
+                      create: fn(&'a Newton, &'c C, Mat4) -> B)
+                      -> Self {
+        Self { newton,
+               collision,
+               create,
+               matrix: [[1.0, 0.0, 0.0, 0.0],
+                        [0.0, 1.0, 0.0, 0.0],
+                        [0.0, 0.0, 1.0, 0.0],
+                        [0.0, 0.0, 0.0, 1.0]],
+               name: None,
+               mass: None,
+               inertia: None,
+               centre_of_mass: None,
+               linear_damping: None,
+               angular_damping: None,
+               continuous: None,
+               material_group: None,
+               sleep_state: None,
+               auto_sleep: None,
+               velocity: None,
+               omega: None,
+               force_and_torque: None,
+               transform: None,
+               destructor: None }
+    }
+
+    /// Sets the full transformation. Overrides `position` & rotation.
+    pub fn matrix(mut self, matrix: Mat4) -> Self {
+        self.matrix = matrix;
+        self
+    }
+
+    pub fn position(mut self, [x, y, z]: Vec3) -> Self {
+        self.matrix[3] = [x, y, z, 1.0];
+        self
+    }
+
+    /// Sets the rotation from a unit quaternion, laid out as `[w, x, y, z]`.
+    pub fn rotation(self, rotation: Quat) -> Self {
+        self.with_rotation(quat_to_mat(rotation))
+    }
+
+    /// Sets the rotation from Euler angles (pitch, yaw & roll), in radians.
+    pub fn euler_angles(self, euler: Vec3) -> Self {
+        self.with_rotation(set_euler_angle(euler))
+    }
+
+    fn with_rotation(mut self, rotation: Mat4) -> Self {
+        for i in 0..3 {
+            self.matrix[i] = rotation[i];
+        }
+        self
+    }
+
+    pub fn name<N: Into<Cow<'static, str>>>(mut self, name: N) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Sets the mass. Unless `inertia` is also set, the inertia & centre of
+    /// mass are computed from the collision.
+    pub fn mass(mut self, mass: f32) -> Self {
+        self.mass = Some(Mass::Mass(mass));
+        self
+    }
+
+    /// Sets the mass from the density and the volume of the collision.
+    pub fn density(mut self, density: f32) -> Self {
+        self.mass = Some(Mass::Density(density));
+        self
+    }
+
+    /// Diagonal of the inertia matrix. Ignored unless the mass or density is set.
+    pub fn inertia(mut self, inertia: Vec3) -> Self {
+        self.inertia = Some(inertia);
+        self
+    }
+
+    /// Centre of mass, in local space.
+    pub fn centre_of_mass(mut self, com: Vec3) -> Self {
+        self.centre_of_mass = Some(com);
+        self
+    }
+
+    pub fn linear_damping(mut self, damping: f32) -> Self {
+        self.linear_damping = Some(damping);
+        self
+    }
+
+    pub fn angular_damping(mut self, damping: Vec3) -> Self {
+        self.angular_damping = Some(damping);
+        self
+    }
+
+    /// Enables continuous collision detection, for small & fast bodies.
+    pub fn continuous(mut self, cont: bool) -> Self {
+        self.continuous = Some(cont);
+        self
+    }
+
+    pub fn material_group_id(mut self, id: i32) -> Self {
+        self.material_group = Some(id);
+        self
+    }
+
+    pub fn sleep_state(mut self, state: SleepState) -> Self {
+        self.sleep_state = Some(state);
+        self
+    }
+
+    pub fn auto_sleep(mut self, auto: bool) -> Self {
+        self.auto_sleep = Some(auto);
+        self
+    }
+
+    pub fn velocity(mut self, velocity: Vec3) -> Self {
+        self.velocity = Some(velocity);
+        self
+    }
+
+    pub fn omega(mut self, omega: Vec3) -> Self {
+        self.omega = Some(omega);
+        self
+    }
+
+    pub fn force_and_torque_callback<F>(mut self, callback: F) -> Self
+        where F: FnMut(Body, Duration, usize) + Send + 'static
+    {
+        self.force_and_torque = Some(Box::new(callback));
+        self
+    }
+
+    pub fn transform_callback<F>(mut self, callback: F) -> Self
+        where F: FnMut(Body, Mat4, usize) + Send + 'static
+    {
+        self.transform = Some(Box::new(callback));
+        self
+    }
+
+    pub fn destroy_callback<F>(mut self, callback: F) -> Self
+        where F: FnMut(Body) + Send + 'static
+    {
+        self.destructor = Some(Box::new(callback));
+        self
+    }
+
+    /// Creates the body.
+    pub fn build(self) -> B {
+        let body = (self.create)(self.newton, self.collision, self.matrix);
+
+        if let Some(name) = self.name {
+            body.set_name(name);
+        }
+
+        match (self.mass, self.inertia) {
+            (Some(Mass::Mass(mass)), Some(inertia)) => body.set_mass_matrix(mass, inertia),
+            (Some(Mass::Mass(mass)), None) => body.set_mass(mass, self.collision),
+            (Some(Mass::Density(density)), Some(inertia)) => {
+                body.set_mass_matrix(density * self.collision.volume(), inertia)
+            }
+            (Some(Mass::Density(density)), None) => {
+                body.set_mass_from_density(density, self.collision)
+            }
+            (None, _) => {}
+        }
+
+        if let Some(com) = self.centre_of_mass {
+            body.set_centre_of_mass(com);
+        }
+        if let Some(damping) = self.linear_damping {
+            body.set_linear_damping(damping);
+        }
+        if let Some(damping) = self.angular_damping {
+            body.set_angular_damping(damping);
+        }
+        if let Some(cont) = self.continuous {
+            body.set_continuous(cont);
+        }
+        if let Some(id) = self.material_group {
+            body.set_material_group_id(id);
+        }
+        if let Some(velocity) = self.velocity {
+            body.set_velocity(velocity);
+        }
+        if let Some(omega) = self.omega {
+            body.set_omega(omega);
+        }
+        if let Some(callback) = self.force_and_torque {
+            body.set_force_and_torque_callback(callback);
+        }
+        if let Some(callback) = self.transform {
+            body.set_transform_callback(callback);
+        }
+        if let Some(callback) = self.destructor {
+            body.set_destroy_callback(callback);
+        }
+        if let Some(auto) = self.auto_sleep {
+            body.set_auto_sleep(auto);
+        }
+        // last, so setting the velocities doesn't wake the body up
+        if let Some(state) = self.sleep_state {
+            body.set_sleep_state(state);
+        }
+
+        body
+    }
+
+    /// Creates the body and moves it to the world storage.
+    pub fn into_handle(self) -> BodyHandle {
+        let newton = self.newton;
+        self.build().into_handle(newton)
+    }
+}
@@ -16,13 +16,18 @@ impl<'a> Iterator for Bodies<'a> {
     type Item = Body<'a>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let current = self.next;
-        if current.is_null() {
-            None
-        } else {
+        loop {
+            let current = self.next;
+            if current.is_null() {
+                return None;
+            }
             unsafe {
                 self.next = ffi::NewtonWorldGetNextBody(self.newton, current);
-                Some(Body::from_raw(current, false))
+
+                // skip bodies of unsupported types
+                if let Some(body) = Body::try_from_raw(current, false) {
+                    return Some(body);
+                }
             }
         }
     }
@@ -1,15 +1,24 @@
+use std::any::Any;
+use std::borrow::Cow;
 use std::marker::PhantomData;
 use std::mem;
-use std::sync::RwLock;
+use std::sync::{Mutex, RwLock};
 use std::time::Duration;
 
-use crate::collision::{Collision, NewtonCollision};
+use crate::collision::{self, ClosestPoint, Collision, Contact, MassProperties, NewtonCollision};
 use crate::ffi;
-use crate::handle::{AsHandle, FromHandle, Handle, IntoHandle};
+use crate::handle::{AsHandle, BodyHandle, FromHandle, IntoHandle};
 use crate::joint::iter::Joints;
-use crate::math::{Mat4, Vec3};
-use crate::newton::Newton;
-
+use crate::math::{length, mat_mul, sub, Mat4, Vec3};
+use crate::newton::commands::Commands;
+use crate::newton::force_field;
+use crate::newton::layers::LAYERS;
+use crate::newton::{CriticalSection, Newton};
+
+/// Keyframed kinematic body animation.
+pub mod animation;
+/// Body builder.
+pub mod builder;
 /// Body iterators.
 pub mod iter;
 
@@ -20,13 +29,6 @@ pub enum SleepState {
     Sleeping = 1,
 }
 
-#[repr(u32)]
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
-enum Type {
-    Dynamic = ffi::NEWTON_DYNAMIC_BODY,
-    Kinematic = ffi::NEWTON_KINEMATIC_BODY,
-}
-
 #[derive(Default)]
 struct UserData {
     /// Callback only applicable to dynamic bodies.
@@ -41,8 +43,21 @@ struct UserData {
     // Type is FnMut because FnOnce cannot be consumed when Boxed.
     destructor: Option<Box<dyn FnMut(Body)>>,
 
-    /// An optional name given to the body when it is created.
-    name: Option<&'static str>,
+    /// Sleep state change callback, and the last state it was notified of.
+    sleep: Option<Box<dyn FnMut(Body, SleepState)>>,
+    last_sleep_state: Option<SleepState>,
+
+    /// An optional name given to the body.
+    name: Option<Cow<'static, str>>,
+
+    /// Collision layer (0-31), and the layers the body is allowed to collide with.
+    layer: u8,
+    collision_mask: u32,
+
+    /// Application data attached to the body. It has its own lock, so the data can be
+    /// read while other properties of the body are updated. A mutex only requires the
+    /// data to be `Send`.
+    user_data: Mutex<Option<Box<dyn Any + Send>>>,
 
     // Lock used to write/read body properties such as position, matrix, collision,
     // etc, ...
@@ -68,11 +83,15 @@ bodies! {
     ///
     /// If a dynamic body has no mass, it is equivalent to a static body.
     #[derive(Debug, Eq, PartialEq)]
-    (Dynamic, NewtonCreateDynamicBody, dynamic) => pub struct DynamicBody<'a>(...);
+    (Dynamic, ffi::NEWTON_DYNAMIC_BODY, NewtonCreateDynamicBody, dynamic, is_dynamic) => pub struct DynamicBody<'a>(...);
 
     /// A body that is not affected by forces and is controlled by the application.
     #[derive(Debug, Eq, PartialEq)]
-    (Kinematic, NewtonCreateKinematicBody, kinematic) => pub struct KinematicBody<'a>(...);
+    (Kinematic, ffi::NEWTON_KINEMATIC_BODY, NewtonCreateKinematicBody, kinematic, is_kinematic) => pub struct KinematicBody<'a>(...);
+
+    /// Soft body. Must be created from a `DeformableSolid` or a `MassSpringDamperSystem` collision.
+    #[derive(Debug, Eq, PartialEq)]
+    (Deformable, ffi::NEWTON_DEFORMABLE_BODY, NewtonCreateDeformableBody, deformable, is_deformable) => pub struct DeformableBody<'a>(...);
 }
 
 macro_rules! lock {
@@ -92,12 +111,107 @@ macro_rules! lock {
     };
 }
 
+// Sets up the user data & destructor callback of a newly created body.
+unsafe fn init_body(body: *const ffi::NewtonBody, name: Option<Cow<'static, str>>) {
+    let userdata = Box::new(UserData { name,
+                                       collision_mask: !0,
+                                       ..Default::default() });
+
+    ffi::NewtonBodySetDestructorCallback(body, Some(body_destructor));
+    ffi::NewtonBodySetForceAndTorqueCallback(body, Some(force_and_torque));
+    ffi::NewtonBodySetUserData(body, mem::transmute(userdata));
+}
+
+impl<'a> DynamicBody<'a> {
+    /// Creates a dynamic body that supports a full (non-diagonal) inertia matrix.
+    ///
+    /// Use `NewtonBody::set_full_mass_matrix` to set the inertia of the body.
+    pub fn create_asymmetric<C, N>(newton: &'a Newton,
+                                   collision: &C,
+                                   matrix: Mat4,
+                                   name: Option<N>)
+                                   -> Self
+        where C: NewtonCollision,
+              N: Into<Cow<'static, str>>
+    {
+        unsafe {
+            let body = ffi::NewtonCreateAsymetricDynamicBody(newton.as_raw(),
+                                                             collision.as_raw(),
+                                                             matrix[0].as_ptr());
+            init_body(body, name.map(Into::into));
+            Self::from_raw(body, true)
+        }
+    }
+}
+
+// Collision pieces of a body, with their world matrices. Compounds are split in their
+// children, which are placed relative to the compound.
+fn pieces<B: NewtonBody + ?Sized>(body: &B) -> Vec<(*const ffi::NewtonCollision, Mat4)> {
+    let matrix = body.matrix();
+    match body.collision() {
+        Collision::Compound(compound) => {
+            let matrix = mat_mul(&matrix, &compound.matrix());
+            compound.collisions().map(|c| (c.as_raw(), matrix)).collect()
+        }
+        collision => vec![(collision.as_raw(), matrix)],
+    }
+}
+
+fn convex_pieces<B: NewtonBody + ?Sized>(body: &B) -> Vec<(*const ffi::NewtonCollision, Mat4)> {
+    pieces(body).into_iter()
+                .filter(|&(col, _)| unsafe { ffi::NewtonCollisionIsConvexShape(col) == 1 })
+                .collect()
+}
+
+/// Calls the sleep callback of the body if its sleep state changed since the last call.
+pub(crate) unsafe fn notify_sleep_state(body: *const ffi::NewtonBody) {
+    let state: SleepState = mem::transmute(ffi::NewtonBodyGetSleepState(body));
+    let mut udata = ffi::NewtonBodyGetUserData(body);
+    let udata: &mut Box<UserData> = mem::transmute(&mut udata);
+    if udata.sleep.is_none() || udata.last_sleep_state == Some(state) {
+        return;
+    }
+    udata.last_sleep_state = Some(state);
+    let body = Body::try_from_raw(body, false);
+    if let (Some(callback), Some(body)) = (&mut udata.sleep, body) {
+        callback(body, state);
+    }
+}
+
+// The force & torque callback is installed on every body, to apply the world gravity
+// and force fields after the application callback (if any).
+unsafe extern "C" fn force_and_torque(body: *const ffi::NewtonBody,
+                                      timestep: f32,
+                                      thread: std::os::raw::c_int) {
+    let seconds = timestep.floor() as u64;
+    let nanos = (timestep.fract() * 1_000_000_000.0) as u32;
+    let timestep = Duration::new(seconds, nanos);
+
+    let mut udata = ffi::NewtonBodyGetUserData(body);
+    let mut udata: &mut Box<UserData> = mem::transmute(&mut udata);
+    let gravity = match (&mut udata.force_and_torque, Body::try_from_raw(body, false)) {
+        (Some(callback), Some(wrapped)) => {
+            callback(wrapped, timestep, thread as usize);
+            false
+        }
+        // the callback can't be given bodies of unsupported types
+        (Some(_), None) => false,
+        (None, _) => true,
+    };
+
+    // fields only use the NewtonBody API, so any wrapper will do
+    force_field::apply(&DynamicBody::from_raw(body, false), gravity);
+}
+
 unsafe extern "C" fn body_destructor(body: *const ffi::NewtonBody) {
     let udata = ffi::NewtonBodyGetUserData(body);
     let mut udata: Box<UserData> = Box::from_raw(udata as _);
+    if udata.sleep.is_some() {
+        Newton::unwatch_sleep_state(body);
+    }
 
-    if let Some(mut destructor) = udata.destructor.take() {
-        let body = Body::from_raw(body, false);
+    let body = Body::try_from_raw(body, false);
+    if let (Some(mut destructor), Some(body)) = (udata.destructor.take(), body) {
         destructor(body);
     }
 }
@@ -154,6 +268,82 @@ pub trait NewtonBody {
         velo
     }
 
+    fn set_velocity(&self, velocity: Vec3) {
+        lock!(self, write);
+        unsafe { ffi::NewtonBodySetVelocity(self.as_raw(), velocity.as_ptr()) }
+    }
+
+    /// Angular velocity, in world space.
+    fn omega(&self) -> Vec3 {
+        lock!(self, read);
+        let mut omega: Vec3 = Default::default();
+        unsafe { ffi::NewtonBodyGetOmega(self.as_raw(), omega.as_mut_ptr()) }
+        omega
+    }
+
+    fn set_omega(&self, omega: Vec3) {
+        lock!(self, write);
+        unsafe { ffi::NewtonBodySetOmega(self.as_raw(), omega.as_ptr()) }
+    }
+
+    /// Velocity of a point of the body, given in world space.
+    fn point_velocity(&self, point: Vec3) -> Vec3 {
+        lock!(self, read);
+        let mut velo: Vec3 = Default::default();
+        unsafe { ffi::NewtonBodyGetPointVelocity(self.as_raw(), point.as_ptr(), velo.as_mut_ptr()) }
+        velo
+    }
+
+    /// Applies the impulse needed to change the velocity of a point of the body
+    /// (world space) by `delta_velocity` over the given timestep.
+    ///
+    /// http://newtondynamics.com/wiki/index.php5?title=NewtonBodyAddImpulse
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use newton::prelude::*;
+    /// use newton::{DynamicBody, Newton, Sphere};
+    ///
+    /// let newton = Newton::create();
+    /// let sphere = Sphere::create(&newton, 1.0, None);
+    /// let body = DynamicBody::builder(&newton, &sphere).mass(1.0).build();
+    ///
+    /// body.set_velocity([1.0, 0.0, 0.0]);
+    /// body.set_omega([0.0, 1.0, 0.0]);
+    /// assert_eq!([1.0, 0.0, 0.0], body.velocity());
+    ///
+    /// // spinning around the y axis, the +x side of the body moves towards -z
+    /// let [x, y, z] = body.point_velocity([1.0, 0.0, 0.0]);
+    /// assert!((x - 1.0).abs() < 1e-4 && y.abs() < 1e-4 && (z + 1.0).abs() < 1e-4);
+    ///
+    /// // pushing the centre of mass only changes the linear velocity
+    /// body.add_impulse([0.0, 2.0, 0.0], [0.0, 0.0, 0.0], Duration::new(0, 1_000_000_000 / 60));
+    /// let [x, y, z] = body.velocity();
+    /// assert!((x - 1.0).abs() < 1e-3 && (y - 2.0).abs() < 1e-3 && z.abs() < 1e-3);
+    /// ```
+    fn add_impulse(&self, delta_velocity: Vec3, point: Vec3, timestep: Duration) {
+        lock!(self, write);
+        let step = timestep.as_secs_f32();
+        unsafe {
+            ffi::NewtonBodyAddImpulse(self.as_raw(), delta_velocity.as_ptr(), point.as_ptr(), step)
+        }
+    }
+
+    /// Applies a linear and an angular impulse to the centre of mass of the body.
+    fn apply_impulse_pair(&self, linear: Vec3, angular: Vec3, timestep: Duration) {
+        lock!(self, write);
+        let step = timestep.as_secs_f32();
+        let mut linear = linear;
+        let mut angular = angular;
+        unsafe {
+            ffi::NewtonBodyApplyImpulsePair(self.as_raw(),
+                                            linear.as_mut_ptr(),
+                                            angular.as_mut_ptr(),
+                                            step)
+        }
+    }
+
     fn aabb(&self) -> (Vec3, Vec3) {
         lock!(self, read);
         let mut min: Vec3 = Default::default();
@@ -164,14 +354,10 @@ pub trait NewtonBody {
         (min, max)
     }
 
-    /*
-    fn body_type(&self) -> Type {
-        unsafe {
-            let btype = ffi::NewtonBodyGetType(self.as_raw());
-            mem::transmute(btype)
-        }
+    /// Returns the type of the body, or `None` if the type is not supported by this crate.
+    fn body_type(&self) -> Option<Type> {
+        unsafe { Type::from_raw(ffi::NewtonBodyGetType(self.as_raw())) }
     }
-    */
 
     fn sleep_state(&self) -> SleepState {
         lock!(self, read);
@@ -199,11 +385,170 @@ pub trait NewtonBody {
         self.set_sleep_state(SleepState::Sleeping)
     }
 
+    fn is_auto_sleep(&self) -> bool {
+        lock!(self, read);
+        unsafe { ffi::NewtonBodyGetAutoSleep(self.as_raw()) == 1 }
+    }
+
+    /// Allows or prevents the body from falling asleep when it comes to rest.
+    fn set_auto_sleep(&self, auto: bool) {
+        lock!(self, write);
+        unsafe {
+            let state = if auto { 1 } else { 0 };
+            ffi::NewtonBodySetAutoSleep(self.as_raw(), state);
+        }
+    }
+
+    fn is_frozen(&self) -> bool {
+        lock!(self, read);
+        unsafe { ffi::NewtonBodyGetFreezeState(self.as_raw()) == 1 }
+    }
+
+    /// Freezes the body in place. Frozen bodies are not woken up by other bodies.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use newton::prelude::*;
+    /// use newton::{DynamicBody, Newton, Sphere};
+    ///
+    /// let mut newton = Newton::config().gravity([0.0, -9.8, 0.0]).build();
+    /// let body = {
+    ///     let sphere = Sphere::create(&newton, 0.5, None);
+    ///     DynamicBody::builder(&newton, &sphere).mass(1.0).into_handle()
+    /// };
+    ///
+    /// newton.storage().body(body).unwrap().set_frozen(true);
+    /// assert!(newton.storage().body(body).unwrap().is_frozen());
+    ///
+    /// newton.update(Duration::new(0, 1_000_000_000 / 60));
+    /// assert_eq!([0.0, 0.0, 0.0], newton.storage().body(body).unwrap().position());
+    ///
+    /// newton.storage().body(body).unwrap().set_frozen(false);
+    /// assert!(!newton.storage().body(body).unwrap().is_frozen());
+    /// ```
+    fn set_frozen(&self, frozen: bool) {
+        lock!(self, write);
+        unsafe {
+            let state = if frozen { 1 } else { 0 };
+            ffi::NewtonBodySetFreezeState(self.as_raw(), state);
+        }
+    }
+
+    fn is_simulated(&self) -> bool {
+        lock!(self, read);
+        unsafe { ffi::NewtonBodyGetSimulationState(self.as_raw()) == 1 }
+    }
+
+    /// Takes the body out of the simulation (or puts it back) without destroying it.
+    ///
+    /// Bodies out of the simulation are neither moved nor collided with.
+    fn set_simulated(&self, simulated: bool) {
+        lock!(self, write);
+        unsafe {
+            let state = if simulated { 1 } else { 0 };
+            ffi::NewtonBodySetSimulationState(self.as_raw(), state);
+        }
+    }
+
+    fn is_collidable(&self) -> bool {
+        lock!(self, read);
+        unsafe { ffi::NewtonBodyGetCollidable(self.as_raw()) == 1 }
+    }
+
+    /// Enables or disables contact generation with other bodies.
+    fn set_collidable(&self, collidable: bool) {
+        lock!(self, write);
+        unsafe {
+            let state = if collidable { 1 } else { 0 };
+            ffi::NewtonBodySetCollidable(self.as_raw(), state);
+        }
+    }
+
+    /// Collision layer of the body. Bodies start on layer 0.
+    fn layer(&self) -> u8 {
+        lock!(self, read);
+        unsafe { (*(ffi::NewtonBodyGetUserData(self.as_raw()) as *const UserData)).layer }
+    }
+
+    /// Moves the body to another collision layer.
+    ///
+    /// Whether two bodies collide depends on the world layer matrix (see
+    /// `Newton::set_layers_collide`) and the collision masks of both bodies.
+    ///
+    /// ## Panics
+    /// Panics if `layer` is not lower than `newton::layers::LAYERS`.
+    fn set_layer(&self, layer: u8) {
+        assert!((layer as usize) < LAYERS, "Invalid collision layer ({})", layer);
+        lock!(self, write);
+        unsafe {
+            let mut udata: &mut Box<UserData> =
+                mem::transmute(&mut ffi::NewtonBodyGetUserData(self.as_raw()));
+            udata.layer = layer;
+        }
+    }
+
+    /// Bitfield of the layers this body can collide with. All set by default.
+    fn collision_mask(&self) -> u32 {
+        lock!(self, read);
+        unsafe { (*(ffi::NewtonBodyGetUserData(self.as_raw()) as *const UserData)).collision_mask }
+    }
+
+    fn set_collision_mask(&self, mask: u32) {
+        lock!(self, write);
+        unsafe {
+            let mut udata: &mut Box<UserData> =
+                mem::transmute(&mut ffi::NewtonBodyGetUserData(self.as_raw()));
+            udata.collision_mask = mask;
+        }
+    }
+
+    fn force(&self) -> Vec3 {
+        lock!(self, read);
+        let mut force: Vec3 = Default::default();
+        unsafe { ffi::NewtonBodyGetForce(self.as_raw(), force.as_mut_ptr()) }
+        force
+    }
+
+    /// Replaces the net force applied to the body.
+    ///
+    /// Only has effect from within the force & torque callback.
     fn set_force(&self, force: Vec3) {
         lock!(self, write);
         unsafe { ffi::NewtonBodySetForce(self.as_raw(), force.as_ptr()) }
     }
 
+    /// Adds a force to the net force applied to the body.
+    ///
+    /// Only has effect from within the force & torque callback.
+    fn add_force(&self, force: Vec3) {
+        lock!(self, write);
+        unsafe { ffi::NewtonBodyAddForce(self.as_raw(), force.as_ptr()) }
+    }
+
+    fn torque(&self) -> Vec3 {
+        lock!(self, read);
+        let mut torque: Vec3 = Default::default();
+        unsafe { ffi::NewtonBodyGetTorque(self.as_raw(), torque.as_mut_ptr()) }
+        torque
+    }
+
+    /// Replaces the net torque applied to the body.
+    ///
+    /// Only has effect from within the force & torque callback.
+    fn set_torque(&self, torque: Vec3) {
+        lock!(self, write);
+        unsafe { ffi::NewtonBodySetTorque(self.as_raw(), torque.as_ptr()) }
+    }
+
+    /// Adds a torque to the net torque applied to the body.
+    ///
+    /// Only has effect from within the force & torque callback.
+    fn add_torque(&self, torque: Vec3) {
+        lock!(self, write);
+        unsafe { ffi::NewtonBodyAddTorque(self.as_raw(), torque.as_ptr()) }
+    }
+
     fn set_collision<C: NewtonCollision>(&self, collision: C) {
         lock!(self, write);
         unsafe { ffi::NewtonBodySetCollision(self.as_raw(), collision.as_raw()) }
@@ -217,11 +562,214 @@ pub trait NewtonBody {
         }
     }
 
-    fn name(&self) -> Option<&'static str> {
+    /// Closest points between the collisions of two bodies, or `None` if they overlap.
+    ///
+    /// Children of compounds are tested one by one. Non-convex shapes (trees, height
+    /// fields, etc) are ignored, so `None` is also returned if either body has none.
+    /// Uses the thread index 0, so it must not be called while the world is being updated.
+    ///
+    /// ```
+    /// use newton::prelude::*;
+    /// use newton::{DynamicBody, Newton, Sphere};
+    ///
+    /// let newton = Newton::create();
+    /// let sphere = Sphere::create(&newton, 1.0, None);
+    ///
+    /// let a = DynamicBody::builder(&newton, &sphere).build();
+    /// let b = DynamicBody::builder(&newton, &sphere).position([5.0, 0.0, 0.0]).build();
+    ///
+    /// let closest = a.distance_to(&b).unwrap();
+    /// assert!((closest.p1[0] - closest.p0[0] - 3.0).abs() < 1e-3);
+    /// assert!(!a.overlaps(&b));
+    /// ```
+    fn distance_to<B: NewtonBody>(&self, other: &B) -> Option<ClosestPoint> {
+        let world = unsafe { ffi::NewtonBodyGetWorld(self.as_raw()) };
+        let (pieces_a, pieces_b) = (convex_pieces(self), convex_pieces(other));
+
+        let mut closest: Option<(f32, ClosestPoint)> = None;
+        for &(col_a, mat0) in &pieces_a {
+            for &(col_b, mat1) in &pieces_b {
+                let mut p0 = [0.0, 0.0, 0.0];
+                let mut p1 = [0.0, 0.0, 0.0];
+                let mut normal = [0.0, 0.0, 0.0];
+                let separated = unsafe {
+                    ffi::NewtonCollisionClosestPoint(world,
+                                                     col_a,
+                                                     mat0[0].as_ptr(),
+                                                     col_b,
+                                                     mat1[0].as_ptr(),
+                                                     p0.as_mut_ptr(),
+                                                     p1.as_mut_ptr(),
+                                                     normal.as_mut_ptr(),
+                                                     0)
+                };
+                if separated == 0 {
+                    return None;
+                }
+                let dist = length(sub(p1, p0));
+                if closest.as_ref().map_or(true, |&(d, _)| dist < d) {
+                    closest = Some((dist, ClosestPoint { p0, mat0, p1, mat1, normal }));
+                }
+            }
+        }
+        closest.map(|(_, point)| point)
+    }
+
+    /// Deepest contact between the collisions of two bodies, or `None` if they don't
+    /// intersect. Children of compounds are tested one by one. Uses the thread index 0,
+    /// like `distance_to`.
+    ///
+    /// ```
+    /// use newton::prelude::*;
+    /// use newton::{Compound, DynamicBody, Newton, Sphere};
+    ///
+    /// let newton = Newton::create();
+    /// let sphere = Sphere::create(&newton, 1.0, None);
+    ///
+    /// // a single child, moved to x = 10 by the local matrix of the compound
+    /// let mut shape = Compound::create(&newton);
+    /// {
+    ///     let mut build = shape.begin_build();
+    ///     build.add(&sphere);
+    /// }
+    /// shape.set_matrix([[1.0, 0.0, 0.0, 0.0],
+    ///                   [0.0, 1.0, 0.0, 0.0],
+    ///                   [0.0, 0.0, 1.0, 0.0],
+    ///                   [10.0, 0.0, 0.0, 1.0]]);
+    /// let compound = DynamicBody::builder(&newton, &shape).build();
+    /// let ball = |x| DynamicBody::builder(&newton, &sphere).position([x, 0.0, 0.0]).build();
+    ///
+    /// // separated
+    /// let far = ball(5.0);
+    /// let closest = compound.distance_to(&far).unwrap();
+    /// assert!((closest.p0[0] - closest.p1[0] - 3.0).abs() < 1e-3);
+    /// assert!(compound.penetration_with(&far).is_none());
+    /// assert!(!compound.overlaps(&far));
+    ///
+    /// // overlapping
+    /// let near = ball(11.5);
+    /// assert!(compound.distance_to(&near).is_none());
+    /// let contact = compound.penetration_with(&near).unwrap();
+    /// assert!((contact.penetration - 0.5).abs() < 1e-2);
+    /// assert!(compound.overlaps(&near));
+    ///
+    /// // where the child would be without the offset
+    /// let origin = ball(0.0);
+    /// assert!(compound.distance_to(&origin).is_some());
+    /// assert!(compound.penetration_with(&origin).is_none());
+    /// assert!(!compound.overlaps(&origin));
+    /// ```
+    fn penetration_with<B: NewtonBody>(&self, other: &B) -> Option<Contact> {
+        let world = unsafe { ffi::NewtonBodyGetWorld(self.as_raw()) };
+        let newton = unsafe { Newton::from_raw(world, false) };
+        let (pieces_a, pieces_b) = (pieces(self), pieces(other));
+
+        let mut deepest: Option<Contact> = None;
+        for &(col_a, mat_a) in &pieces_a {
+            for &(col_b, mat_b) in &pieces_b {
+                let (col_a, col_b) =
+                    unsafe { (Collision::from_raw(col_a, false), Collision::from_raw(col_b, false)) };
+                for contact in collision::collide(&newton, &col_a, mat_a, &col_b, mat_b, 16, 0) {
+                    if deepest.as_ref().map_or(true, |d| contact.penetration > d.penetration) {
+                        deepest = Some(contact);
+                    }
+                }
+            }
+        }
+        deepest
+    }
+
+    /// Tests whether the collisions of two bodies intersect. Uses the thread index 0,
+    /// like `distance_to`.
+    fn overlaps<B: NewtonBody>(&self, other: &B) -> bool {
+        let world = unsafe { ffi::NewtonBodyGetWorld(self.as_raw()) };
+        let (pieces_a, pieces_b) = (pieces(self), pieces(other));
+        pieces_a.iter().any(|&(col_a, mat_a)| {
+                            pieces_b.iter().any(|&(col_b, mat_b)| unsafe {
+                                               ffi::NewtonCollisionIntersectionTest(world,
+                                                                                    col_a,
+                                                                                    mat_a[0].as_ptr(),
+                                                                                    col_b,
+                                                                                    mat_b[0].as_ptr(),
+                                                                                    0)
+                                               == 1
+                                           })
+                        })
+    }
+
+    /// Returns a copy of the name of the body. Static names are not reallocated.
+    fn name(&self) -> Option<Cow<'static, str>> {
+        unsafe {
+            let udata = &*(ffi::NewtonBodyGetUserData(self.as_raw()) as *const UserData);
+            udata.name.clone()
+        }
+    }
+
+    /// Names (or renames) the body.
+    ///
+    /// Names don't need to be unique. Bodies in the world storage can be looked up by
+    /// name with `Newton::find_body` and `Newton::find_bodies`.
+    fn set_name<N: Into<Cow<'static, str>>>(&self, name: N) {
+        lock!(self, write);
         unsafe {
-            let udata = &ffi::NewtonBodyGetUserData(self.as_raw());
-            let udata: &Box<UserData> = mem::transmute(udata);
-            udata.name
+            let mut udata: &mut Box<UserData> =
+                mem::transmute(&mut ffi::NewtonBodyGetUserData(self.as_raw()));
+
+            let old = udata.name.replace(name.into());
+            let storage = Newton::storage_from_world(ffi::NewtonBodyGetWorld(self.as_raw()));
+            storage.rename_body(BodyHandle::from_ptr(self.as_raw() as _),
+                                old.as_ref().map(|name| &name[..]),
+                                udata.name.as_ref().map(|name| &name[..]));
+        }
+    }
+
+    /// Attaches application data to the body (an entity ID, a gameplay component, etc),
+    /// replacing any previously attached data.
+    fn set_user_data<T: Any + Send>(&self, data: T) {
+        unsafe {
+            let udata = &*(ffi::NewtonBodyGetUserData(self.as_raw()) as *const UserData);
+            *udata.user_data.lock().unwrap() = Some(Box::new(data));
+        }
+    }
+
+    /// Calls `f` with the data attached with `set_user_data`, or with `None` if there is
+    /// no data, or if it is not of type `T`.
+    ///
+    /// The data is locked while `f` runs, so calling `set_user_data` or `with_user_data`
+    /// on the same body from within `f` deadlocks.
+    ///
+    /// ```
+    /// use newton::prelude::*;
+    /// use newton::{DynamicBody, Newton, Sphere};
+    ///
+    /// struct Entity(u32);
+    ///
+    /// let newton = Newton::create();
+    /// let sphere = Sphere::create(&newton, 1.0, None);
+    /// let body = DynamicBody::builder(&newton, &sphere).build();
+    ///
+    /// assert_eq!(None, body.with_user_data(|e: Option<&Entity>| e.map(|e| e.0)));
+    ///
+    /// body.set_user_data(Entity(42));
+    /// assert_eq!(Some(42), body.with_user_data(|e: Option<&Entity>| e.map(|e| e.0)));
+    ///
+    /// // data of a different type is not returned
+    /// assert!(body.with_user_data(|n: Option<&u32>| n.is_none()));
+    ///
+    /// // the data doesn't need to be Sync
+    /// use std::cell::Cell;
+    /// body.set_user_data(Cell::new(1));
+    /// body.with_user_data(|c: Option<&Cell<i32>>| c.unwrap().set(2));
+    /// assert_eq!(Some(2), body.with_user_data(|c: Option<&Cell<i32>>| c.map(Cell::get)));
+    /// ```
+    fn with_user_data<T, R, F>(&self, f: F) -> R
+        where T: Any + Send,
+              F: FnOnce(Option<&T>) -> R
+    {
+        unsafe {
+            let udata = &*(ffi::NewtonBodyGetUserData(self.as_raw()) as *const UserData);
+            let data = udata.user_data.lock().unwrap();
+            f(data.as_ref().and_then(|data| data.downcast_ref::<T>()))
         }
     }
 
@@ -241,6 +789,97 @@ pub trait NewtonBody {
         }
     }
 
+    /// Sets the mass, inertia, and centre of mass of the body.
+    fn set_mass_properties(&self, props: MassProperties) {
+        self.set_mass_matrix(props.mass, props.inertia);
+        self.set_centre_of_mass(props.centre_of_mass);
+    }
+
+    /// Sets the mass properties from the density of the body and the volume of the collision.
+    ///
+    /// Compound children all get the same density. For different densities, use
+    /// `Compound::mass_properties_with` and `set_mass_properties`.
+    fn set_mass_from_density<C: NewtonCollision>(&self, density: f32, collision: &C) {
+        self.set_mass_properties(collision.mass_properties(density));
+    }
+
+    /// Sets the mass and the diagonal of the inertia matrix.
+    fn set_mass_matrix(&self, mass: f32, inertia: Vec3) {
+        lock!(self, write);
+        let [ixx, iyy, izz] = inertia;
+        unsafe { ffi::NewtonBodySetMassMatrix(self.as_raw(), mass, ixx, iyy, izz) }
+    }
+
+    /// Sets the mass and the full inertia matrix.
+    ///
+    /// The off-diagonal terms only have effect on bodies created with
+    /// `DynamicBody::create_asymmetric`.
+    fn set_full_mass_matrix(&self, mass: f32, inertia: Mat4) {
+        lock!(self, write);
+        unsafe { ffi::NewtonBodySetFullMassMatrix(self.as_raw(), mass, inertia[0].as_ptr()) }
+    }
+
+    /// Centre of mass, in local space.
+    fn centre_of_mass(&self) -> Vec3 {
+        lock!(self, read);
+        let mut com: Vec3 = Default::default();
+        unsafe { ffi::NewtonBodyGetCentreOfMass(self.as_raw(), com.as_mut_ptr()) }
+        com
+    }
+
+    fn set_centre_of_mass(&self, com: Vec3) {
+        lock!(self, write);
+        unsafe { ffi::NewtonBodySetCentreOfMass(self.as_raw(), com.as_ptr()) }
+    }
+
+    fn linear_damping(&self) -> f32 {
+        lock!(self, read);
+        unsafe { ffi::NewtonBodyGetLinearDamping(self.as_raw()) }
+    }
+
+    /// Sets the linear drag coefficient, in the range [0, 1].
+    fn set_linear_damping(&self, damping: f32) {
+        lock!(self, write);
+        unsafe { ffi::NewtonBodySetLinearDamping(self.as_raw(), damping) }
+    }
+
+    fn angular_damping(&self) -> Vec3 {
+        lock!(self, read);
+        let mut damping: Vec3 = Default::default();
+        unsafe { ffi::NewtonBodyGetAngularDamping(self.as_raw(), damping.as_mut_ptr()) }
+        damping
+    }
+
+    /// Sets the angular drag coefficients (one per axis), in the range [0, 1].
+    fn set_angular_damping(&self, damping: Vec3) {
+        lock!(self, write);
+        unsafe { ffi::NewtonBodySetAngularDamping(self.as_raw(), damping.as_ptr()) }
+    }
+
+    fn is_gyroscopic_torque(&self) -> bool {
+        lock!(self, read);
+        unsafe { ffi::NewtonBodyGetGyroscopicTorque(self.as_raw()) == 1 }
+    }
+
+    /// Enables or disables the gyroscopic torque of the body.
+    fn set_gyroscopic_torque(&self, gyro: bool) {
+        lock!(self, write);
+        unsafe {
+            let state = if gyro { 1 } else { 0 };
+            ffi::NewtonBodySetGyroscopicTorque(self.as_raw(), state);
+        }
+    }
+
+    fn material_group_id(&self) -> i32 {
+        lock!(self, read);
+        unsafe { ffi::NewtonBodyGetMaterialGroupID(self.as_raw()) }
+    }
+
+    fn set_material_group_id(&self, id: i32) {
+        lock!(self, write);
+        unsafe { ffi::NewtonBodySetMaterialGroupID(self.as_raw(), id) }
+    }
+
     fn set_destroy_callback<F>(&self, callback: F)
         where F: FnMut(Body) + Send + 'static
     {
@@ -253,6 +892,58 @@ pub trait NewtonBody {
         }
     }
 
+    /// Sets a callback that is called whenever the body falls asleep or wakes up.
+    ///
+    /// Changes are detected at the end of `Newton::update` (or when an asynchronous update
+    /// finishes), so the callback runs on the thread that updates the world.
+    ///
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use std::time::Duration;
+    ///
+    /// use newton::prelude::*;
+    /// use newton::body::SleepState;
+    /// use newton::{DynamicBody, Newton, Sphere};
+    ///
+    /// let mut newton = Newton::create();
+    /// let sphere = Sphere::create(&newton, 0.5, None);
+    /// let body = DynamicBody::builder(&newton, &sphere).mass(1.0).into_handle();
+    ///
+    /// let states = Arc::new(Mutex::new(Vec::new()));
+    /// let sink = states.clone();
+    /// newton.storage()
+    ///       .body(body)
+    ///       .unwrap()
+    ///       .set_sleep_callback(move |_, state| sink.lock().unwrap().push(state));
+    ///
+    /// // a body at rest falls asleep after a few steps
+    /// for _ in 0..600 {
+    ///     newton.update(Duration::new(0, 1_000_000_000 / 60));
+    ///     if !states.lock().unwrap().is_empty() {
+    ///         break;
+    ///     }
+    /// }
+    /// assert_eq!(vec![SleepState::Sleeping], *states.lock().unwrap());
+    ///
+    /// newton.storage().body(body).unwrap().set_sleep_state(SleepState::Active);
+    /// newton.storage().body(body).unwrap().set_velocity([1.0, 0.0, 0.0]);
+    /// newton.update(Duration::new(0, 1_000_000_000 / 60));
+    /// assert_eq!(vec![SleepState::Sleeping, SleepState::Active], *states.lock().unwrap());
+    /// ```
+    fn set_sleep_callback<F>(&self, callback: F)
+        where F: FnMut(Body, SleepState) + Send + 'static
+    {
+        let state = self.sleep_state();
+        lock!(self, write);
+        unsafe {
+            let mut udata: &mut Box<UserData> =
+                mem::transmute(&mut ffi::NewtonBodyGetUserData(self.as_raw()));
+            udata.sleep = Some(Box::new(callback));
+            udata.last_sleep_state = Some(state);
+            Newton::watch_sleep_state(self.as_raw());
+        }
+    }
+
     fn set_transform_callback<F>(&self, callback: F)
         where F: FnMut(Body, Mat4, usize) + Send + 'static
     {
@@ -270,13 +961,32 @@ pub trait NewtonBody {
             let mut udata = ffi::NewtonBodyGetUserData(body);
             let udata: &mut Box<UserData> = mem::transmute(&mut udata);
 
-            if let Some(callback) = &mut udata.transform {
+            let body = Body::try_from_raw(body, false);
+            if let (Some(callback), Some(body)) = (&mut udata.transform, body) {
                 let matrix = mem::transmute::<_, &Mat4>(matrix).clone();
-                callback(Body::from_raw(body, false), matrix, thread as _);
+                callback(body, matrix, thread as _);
             }
         }
     }
 
+    /// Enters the critical section of the world the body belongs to.
+    ///
+    /// Intended to be used from the force & torque and transform callbacks, where
+    /// `thread_idx` is the thread index passed to the callback.
+    fn critical_section(&self, thread_idx: usize) -> CriticalSection {
+        unsafe {
+            let world = ffi::NewtonBodyGetWorld(self.as_raw());
+            CriticalSection::lock(world, thread_idx)
+        }
+    }
+
+    /// Returns the deferred mutations buffer of the world the body belongs to.
+    ///
+    /// Use it to create or destroy bodies & joints from within callbacks.
+    fn commands(&self) -> Commands {
+        unsafe { Commands::from_world(ffi::NewtonBodyGetWorld(self.as_raw())) }
+    }
+
     fn joints(&self) -> Joints {
         let joint = unsafe { ffi::NewtonBodyGetFirstJoint(self.as_raw()) };
         Joints { joint, body: self.as_raw(), _phantom: PhantomData }
@@ -292,27 +1002,5 @@ pub trait NewtonBody {
             udata.force_and_torque = Some(Box::new(callback));
             ffi::NewtonBodySetForceAndTorqueCallback(self.as_raw(), Some(force_and_torque));
         }
-
-        unsafe extern "C" fn force_and_torque(body: *const ffi::NewtonBody,
-                                              timestep: f32,
-                                              thread: std::os::raw::c_int) {
-            let seconds = timestep.floor() as u64;
-            let nanos = (timestep.fract() * 1_000_000_000.0) as u32;
-            let timestep = Duration::new(seconds, nanos);
-
-            let mut udata = ffi::NewtonBodyGetUserData(body);
-            let mut udata: &mut Box<UserData> = mem::transmute(&mut udata);
-            if let Some(callback) = &mut udata.force_and_torque {
-                let body = match mem::transmute::<_, Type>(ffi::NewtonBodyGetType(body)) {
-                    Type::Dynamic => Body::Dynamic(DynamicBody { raw: body,
-                                                                 _phantom: PhantomData,
-                                                                 owned: false }),
-                    Type::Kinematic => Body::Kinematic(KinematicBody { raw: body,
-                                                                       _phantom: PhantomData,
-                                                                       owned: false }),
-                };
-                callback(body, timestep, thread as usize);
-            }
-        }
     }
 }
@@ -3,8 +3,11 @@ use std::mem;
 use crate::ffi;
 use crate::handle::{Handle, HandleInner};
 use crate::math::Vec3;
+use crate::newton::Newton;
 
-use super::{Compound, ConvexShape, NewtonCollision, Scene, Tree};
+use super::heightmap::Heightmap;
+use super::{Compound, ConvexShape, Elevation, HeightField, HeightFieldGrid, NewtonCollision, Scene,
+            Tree};
 
 /// Type to add/remove collisions from a compound.
 pub struct CompoundBuilder<'a, 'b> {
@@ -22,6 +25,115 @@ pub struct TreeBuilder<'a, 'b> {
     pub(super) tree: &'b Tree<'a>,
 }
 
+/// Type to configure a height field collision, returned by `HeightField::builder`.
+#[derive(Debug)]
+pub struct HeightFieldBuilder<'a, T> {
+    newton: &'a Newton,
+    width: usize,
+    height: usize,
+    grid: HeightFieldGrid,
+    elevation: Option<Vec<T>>,
+    attributes: Option<Vec<i8>>,
+    vertical_scale: f32,
+    horizontal_scale: (f32, f32),
+    displacement: Option<(Vec<u16>, f32)>,
+}
+
+impl<'a, T: Elevation + Clone + Default> HeightFieldBuilder<'a, T> {
+    pub(super) fn new(newton: &'a Newton, width: usize, height: usize) -> Self {
+        Self { newton,
+               width,
+               height,
+               grid: HeightFieldGrid::NormalDiagonals,
+               elevation: None,
+               attributes: None,
+               vertical_scale: 1.0,
+               horizontal_scale: (1.0, 1.0),
+               displacement: None }
+    }
+
+    pub fn grid(mut self, grid: HeightFieldGrid) -> Self {
+        self.grid = grid;
+        self
+    }
+
+    /// Elevation of every vertex, row by row. Flat if not set.
+    pub fn elevation(mut self, elevation: Vec<T>) -> Self {
+        self.elevation = Some(elevation);
+        self
+    }
+
+    /// Attribute (face id) of every grid cell. Zero if not set.
+    pub fn attributes(mut self, attributes: Vec<i8>) -> Self {
+        self.attributes = Some(attributes);
+        self
+    }
+
+    pub fn vertical_scale(mut self, scale: f32) -> Self {
+        self.vertical_scale = scale;
+        self
+    }
+
+    /// Distance between grid vertices, along the x & z axes.
+    pub fn horizontal_scale(mut self, x: f32, z: f32) -> Self {
+        self.horizontal_scale = (x, z);
+        self
+    }
+
+    /// Horizontal offsets of every vertex, one `u16` per vertex packing the x & z
+    /// offsets as two `i8`. See `HeightField::set_horizontal_displacement`.
+    pub fn horizontal_displacement(mut self, map: Vec<u16>, scale: f32) -> Self {
+        self.displacement = Some((map, scale));
+        self
+    }
+
+    /// Creates the collision.
+    ///
+    /// ## Panics
+    /// Panics if the elevation, attributes, or displacement don't match the grid size.
+    pub fn build(self) -> HeightField<'a, T> {
+        let len = self.width * self.height;
+        let elevation = self.elevation.unwrap_or_else(|| vec![T::default(); len]);
+        let attributes = self.attributes.unwrap_or_else(|| vec![0; len]);
+        let (x, z) = self.horizontal_scale;
+
+        let field = HeightField::create(self.newton,
+                                        self.width,
+                                        self.height,
+                                        self.grid,
+                                        &elevation,
+                                        &attributes,
+                                        self.vertical_scale,
+                                        x,
+                                        z);
+        if let Some((map, scale)) = self.displacement {
+            field.set_horizontal_displacement(&map, scale);
+        }
+        field
+    }
+}
+
+impl<'a> HeightFieldBuilder<'a, u16> {
+    /// Takes the grid size & elevation from a heightmap image.
+    pub fn heightmap(mut self, map: &Heightmap) -> Self {
+        self.width = map.width;
+        self.height = map.height;
+        self.elevation = Some(map.data.clone());
+        self
+    }
+}
+
+impl<'a> HeightFieldBuilder<'a, f32> {
+    /// Takes the grid size & elevation from a heightmap image. Elevation is normalized to
+    /// the [0, 1] range, so set the vertical scale to the height of the terrain.
+    pub fn heightmap(mut self, map: &Heightmap) -> Self {
+        self.width = map.width;
+        self.height = map.height;
+        self.elevation = Some(map.normalized());
+        self
+    }
+}
+
 impl<'a, 'b> CompoundBuilder<'a, 'b> {
     /// Adds a collision to the compound
     pub fn add<C>(&self, col: &C) -> Handle
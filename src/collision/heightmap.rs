@@ -0,0 +1,214 @@
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+
+/// Error returned when a heightmap can't be loaded.
+#[derive(Debug)]
+pub enum HeightmapError {
+    Io(io::Error),
+    /// The PGM header is missing or malformed, or the size of the image overflows.
+    InvalidHeader,
+    /// The file holds fewer samples than `width * height`.
+    MissingData { expected: usize, found: usize },
+}
+
+impl fmt::Display for HeightmapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HeightmapError::Io(err) => write!(f, "{}", err),
+            HeightmapError::InvalidHeader => write!(f, "Invalid PGM header"),
+            HeightmapError::MissingData { expected, found } => {
+                write!(f, "Expected {} heightmap samples, found {}", expected, found)
+            }
+        }
+    }
+}
+
+impl Error for HeightmapError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            HeightmapError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for HeightmapError {
+    fn from(err: io::Error) -> Self {
+        HeightmapError::Io(err)
+    }
+}
+
+/// Sample size of raw heightmaps.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum BitDepth {
+    Eight,
+    /// Little-endian 16-bit samples.
+    Sixteen,
+}
+
+/// Grayscale elevation image, to be fed to `HeightFieldBuilder::heightmap`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heightmap {
+    pub width: usize,
+    pub height: usize,
+    /// Samples, row by row.
+    pub data: Vec<u16>,
+    /// Value of the highest possible sample (255 for 8-bit images, 65535 for 16-bit).
+    pub max_value: u16,
+}
+
+impl Heightmap {
+    /// Loads a binary (P5) PGM image. Samples of 16-bit images are big-endian, as the
+    /// format mandates.
+    ///
+    /// ```
+    /// use newton::collision::heightmap::{Heightmap, HeightmapError};
+    ///
+    /// // 8-bit samples, with comments in the header
+    /// let pgm = b"P5 # exported\n2 2\n# max\n255\n\x00\x33\x66\xff";
+    /// let map = Heightmap::from_pgm(&pgm[..]).unwrap();
+    /// assert_eq!((2, 2, 255), (map.width, map.height, map.max_value));
+    /// assert_eq!(vec![0x00, 0x33, 0x66, 0xff], map.data);
+    /// assert_eq!(vec![0.0, 0.2, 0.4, 1.0], map.normalized());
+    ///
+    /// // 16-bit big-endian samples, scaled by the max value
+    /// let pgm = b"P5\n2 1\n1000\n\x01\xf4\x03\xe8";
+    /// let map = Heightmap::from_pgm(&pgm[..]).unwrap();
+    /// assert_eq!(vec![500, 1000], map.data);
+    /// assert_eq!(vec![0.5, 1.0], map.normalized());
+    ///
+    /// match Heightmap::from_pgm(&b"P5\n2 2\n255\n\x00\x01\x02"[..]) {
+    ///     Err(HeightmapError::MissingData { expected: 4, found: 3 }) => {}
+    ///     other => panic!("{:?}", other),
+    /// }
+    /// match Heightmap::from_pgm(&b"P2\n1 1\n255\n0"[..]) {
+    ///     Err(HeightmapError::InvalidHeader) => {}
+    ///     other => panic!("{:?}", other),
+    /// }
+    /// // width * height overflows
+    /// let pgm = format!("P5\n{} 2\n255\n", usize::max_value());
+    /// match Heightmap::from_pgm(pgm.as_bytes()) {
+    ///     Err(HeightmapError::InvalidHeader) => {}
+    ///     other => panic!("{:?}", other),
+    /// }
+    /// ```
+    pub fn from_pgm<R: Read>(mut reader: R) -> Result<Self, HeightmapError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+
+        // header: magic, width, height & max value, separated by whitespace. Comments
+        // run from '#' to the end of the line.
+        let mut pos = 0;
+        let mut fields = [0usize; 3];
+        let magic = next_token(&bytes, &mut pos).ok_or(HeightmapError::InvalidHeader)?;
+        if magic != b"P5" {
+            return Err(HeightmapError::InvalidHeader);
+        }
+        for field in fields.iter_mut() {
+            *field = next_token(&bytes, &mut pos).and_then(|t| std::str::from_utf8(t).ok())
+                                                 .and_then(|t| t.parse().ok())
+                                                 .ok_or(HeightmapError::InvalidHeader)?;
+        }
+        let [width, height, max_value] = fields;
+        if max_value == 0 || max_value > 65535 {
+            return Err(HeightmapError::InvalidHeader);
+        }
+
+        // a single whitespace character separates the header from the samples
+        let samples = &bytes[(pos + 1).min(bytes.len())..];
+        let len = width.checked_mul(height).ok_or(HeightmapError::InvalidHeader)?;
+        let data = if max_value < 256 {
+            decode(samples, len, BitDepth::Eight, false)?
+        } else {
+            decode(samples, len, BitDepth::Sixteen, true)?
+        };
+
+        Ok(Self { width, height, data, max_value: max_value as u16 })
+    }
+
+    /// Loads a headerless heightmap (the `.raw` files exported by most terrain tools).
+    pub fn from_raw<R: Read>(mut reader: R,
+                             width: usize,
+                             height: usize,
+                             depth: BitDepth)
+                             -> Result<Self, HeightmapError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        let len = width.checked_mul(height).ok_or(HeightmapError::InvalidHeader)?;
+        let data = decode(&bytes, len, depth, false)?;
+        let max_value = match depth {
+            BitDepth::Eight => 255,
+            BitDepth::Sixteen => 65535,
+        };
+        Ok(Self { width, height, data, max_value })
+    }
+
+    pub fn open_pgm<P: AsRef<Path>>(path: P) -> Result<Self, HeightmapError> {
+        Self::from_pgm(BufReader::new(File::open(path)?))
+    }
+
+    pub fn open_raw<P: AsRef<Path>>(path: P,
+                                    width: usize,
+                                    height: usize,
+                                    depth: BitDepth)
+                                    -> Result<Self, HeightmapError> {
+        Self::from_raw(BufReader::new(File::open(path)?), width, height, depth)
+    }
+
+    /// Samples mapped to the [0, 1] range.
+    pub fn normalized(&self) -> Vec<f32> {
+        let max = f32::from(self.max_value);
+        self.data.iter().map(|&s| f32::from(s) / max).collect()
+    }
+}
+
+fn next_token<'a>(bytes: &'a [u8], pos: &mut usize) -> Option<&'a [u8]> {
+    loop {
+        match bytes.get(*pos)? {
+            b'#' => {
+                while *bytes.get(*pos)? != b'\n' {
+                    *pos += 1;
+                }
+            }
+            c if c.is_ascii_whitespace() => *pos += 1,
+            _ => break,
+        }
+    }
+    let start = *pos;
+    while bytes.get(*pos).map_or(false, |c| !c.is_ascii_whitespace()) {
+        *pos += 1;
+    }
+    Some(&bytes[start..*pos])
+}
+
+fn decode(bytes: &[u8],
+          len: usize,
+          depth: BitDepth,
+          big_endian: bool)
+          -> Result<Vec<u16>, HeightmapError> {
+    let size = match depth {
+        BitDepth::Eight => 1,
+        BitDepth::Sixteen => 2,
+    };
+    let expected = len.checked_mul(size).ok_or(HeightmapError::InvalidHeader)?;
+    if bytes.len() < expected {
+        return Err(HeightmapError::MissingData { expected: len, found: bytes.len() / size });
+    }
+
+    let data = match depth {
+        BitDepth::Eight => bytes[..len].iter().map(|&b| u16::from(b)).collect(),
+        BitDepth::Sixteen => bytes[..expected].chunks(2)
+                                              .map(|b| {
+                                                  if big_endian {
+                                                      u16::from_be_bytes([b[0], b[1]])
+                                                  } else {
+                                                      u16::from_le_bytes([b[0], b[1]])
+                                                  }
+                                              })
+                                              .collect(),
+    };
+    Ok(data)
+}
@@ -1,20 +1,27 @@
+use std::borrow::Cow;
 use std::marker::PhantomData;
 use std::mem;
 use std::os::raw;
+use std::time::Duration;
 
 use crate::body::Body;
 use crate::ffi;
-use crate::handle::{AsHandle, FromHandle, Handle, HandleInner, IntoHandle};
-use crate::math::{Mat4, Vec3};
+use crate::handle::{AsHandle, CollisionHandle, FromHandle, Handle, HandleInner, IntoHandle};
+use crate::math::{rotate, sub, untransform, Mat4, Vec3};
+use crate::mesh::Mesh;
 use crate::newton::Newton;
 
-use builder::{CompoundBuilder, SceneBuilder, TreeBuilder};
+use builder::{CompoundBuilder, HeightFieldBuilder, SceneBuilder, TreeBuilder};
 use iter::{Collisions, Handles};
 
 /// Types to build compounds, scenes, and tree collisions.
 pub mod builder;
+/// Heightmap image loading.
+pub mod heightmap;
 /// Collision & collision handles iterators
 pub mod iter;
+/// Application-defined mesh collisions.
+pub mod user_mesh;
 
 collision! {
     {
@@ -149,7 +156,12 @@ collision! {
         #[derive(Debug, Eq, PartialEq)]
         struct ConvexHull<'a>
         const ffi::SERIALIZE_ID_CONVEXHULL
-        params ConvexHull { }
+        params ConvexHull {
+            vertices: Cow<'a, [Vec3]>,
+            /// Always zero in the params read from a collision, whose vertices are
+            /// already simplified.
+            tolerance: f32,
+        }
     }
 
 /*
@@ -192,6 +204,88 @@ collision! {
     }
 }
 
+impl<'a> Collision<'a> {
+    /// Creates a collision from its parameters. The inverse of `NewtonCollision::params`.
+    ///
+    /// Returns `None` for shapes that can't be described by their parameters alone
+    /// (compounds, scenes, trees, user meshes & deformables), and for degenerate convex
+    /// hulls.
+    ///
+    /// ```
+    /// use newton::prelude::*;
+    /// use newton::collision::Params;
+    /// use newton::{Collision, Newton};
+    ///
+    /// let newton = Newton::create();
+    ///
+    /// let params = Params::Capsule { radius0: 0.5, radius1: 0.5, height: 2.0 };
+    /// let capsule = Collision::from_params(&newton, &params, None).unwrap();
+    ///
+    /// assert_eq!(params, capsule.params());
+    ///
+    /// let vertices = vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+    /// let params = Params::ConvexHull { vertices: vertices.into(), tolerance: 0.0 };
+    /// let hull = Collision::from_params(&newton, &params, None).unwrap();
+    ///
+    /// match hull.params() {
+    ///     Params::ConvexHull { vertices, tolerance } => {
+    ///         assert_eq!(4, vertices.len());
+    ///         assert_eq!(0.0, tolerance);
+    ///     }
+    ///     _ => panic!("not a convex hull"),
+    /// }
+    /// ```
+    pub fn from_params(newton: &'a Newton, params: &Params, offset: Option<Mat4>) -> Option<Self> {
+        let collision: Collision = match *params {
+            Params::Cuboid { dx, dy, dz } => Cuboid::create(newton, dx, dy, dz, offset).into(),
+            Params::Sphere { radius } => Sphere::create(newton, radius, offset).into(),
+            Params::Cylinder { radius0, radius1, height } => {
+                Cylinder::create(newton, radius0, radius1, height, offset).into()
+            }
+            Params::ChamferCylinder { radius, height } => {
+                ChamferCylinder::create(newton, radius, height, offset).into()
+            }
+            Params::Capsule { radius0, radius1, height } => {
+                Capsule::create(newton, radius0, radius1, height, offset).into()
+            }
+            Params::Cone { radius, height } => Cone::create(newton, radius, height, offset).into(),
+            Params::ConvexHull { ref vertices, tolerance } => {
+                ConvexHull::create(newton, vertices, tolerance, offset)?.into()
+            }
+            Params::Null {} => Null::create(newton).into(),
+            Params::HeightFieldF32(ref p) => height_field_from_params(newton, p, offset).into(),
+            Params::HeightFieldU16(ref p) => height_field_from_params(newton, p, offset).into(),
+            _ => return None,
+        };
+        Some(collision)
+    }
+}
+
+fn height_field_from_params<'a, T>(newton: &'a Newton,
+                                   params: &HeightFieldParams<T>,
+                                   offset: Option<Mat4>)
+                                   -> HeightField<'a, T>
+    where T: Elevation + Clone
+{
+    let field = HeightField::create(newton,
+                                    params.width,
+                                    params.height,
+                                    params.grid,
+                                    &params.vertical_elevation,
+                                    &params.attributes,
+                                    params.vertical_scale,
+                                    params.horizontal_scale_x,
+                                    params.horizontal_scale_z);
+    if !params.horizontal_displacement.is_empty() {
+        field.set_horizontal_displacement(&params.horizontal_displacement,
+                                          params.horizontal_displacement_scale);
+    }
+    if let Some(offset) = offset {
+        field.set_matrix(offset);
+    }
+    field
+}
+
 impl<'a> Compound<'a> {
     pub fn create(newton: &'a Newton) -> Self {
         unsafe {
@@ -244,6 +338,72 @@ impl<'a> Compound<'a> {
         }
         CompoundBuilder { compound: self }
     }
+
+    /// Sums the mass properties of the children, each with its own density.
+    ///
+    /// The inertia of every child is moved to the common centre of mass with the
+    /// parallel axis theorem.
+    ///
+    /// ```
+    /// use newton::prelude::*;
+    /// use newton::{Compound, Cuboid, Newton};
+    ///
+    /// let newton = Newton::create();
+    /// let mut compound = Compound::create(&newton);
+    ///
+    /// let wood = Cuboid::create(&newton, 1.0, 1.0, 1.0, None);
+    /// wood.set_user_id(0);
+    /// let steel = Cuboid::create(&newton, 1.0, 1.0, 1.0, None);
+    /// steel.set_user_id(1);
+    ///
+    /// {
+    ///     let mut build = compound.begin_build();
+    ///     build.add(&wood);
+    ///     build.add(&steel);
+    /// }
+    ///
+    /// let props = compound.mass_properties_with(|_, col| match col.user_id() {
+    ///     0 => 700.0,
+    ///     _ => 7850.0,
+    /// });
+    /// assert_eq!(8550.0, props.mass);
+    /// ```
+    pub fn mass_properties_with<F>(&self, mut density: F) -> MassProperties
+        where F: FnMut(Handle, &Collision) -> f32
+    {
+        let children: Vec<_> = self.handles()
+                                   .filter_map(|h| self.get(h).map(|c| (h, c)))
+                                   .map(|(h, c)| {
+                                       let d = density(h, &c);
+                                       c.mass_properties(d)
+                                   })
+                                   .collect();
+
+        let mass: f32 = children.iter().map(|c| c.mass).sum();
+        if mass <= 0.0 {
+            return MassProperties { mass: 0.0,
+                                    inertia: [0.0, 0.0, 0.0],
+                                    centre_of_mass: [0.0, 0.0, 0.0] };
+        }
+
+        let mut com = [0.0, 0.0, 0.0];
+        for child in &children {
+            for i in 0..3 {
+                com[i] += child.centre_of_mass[i] * child.mass / mass;
+            }
+        }
+
+        let mut inertia = [0.0, 0.0, 0.0];
+        for child in &children {
+            let [rx, ry, rz] = sub(child.centre_of_mass, com);
+            let [ix, iy, iz] = child.inertia;
+            inertia[0] += ix + child.mass * (ry * ry + rz * rz);
+            inertia[1] += iy + child.mass * (rx * rx + rz * rz);
+            inertia[2] += iz + child.mass * (rx * rx + ry * ry);
+        }
+
+        MassProperties { mass, inertia, centre_of_mass: com }
+    }
 }
 
 impl<'a> Scene<'a> {
@@ -314,6 +474,94 @@ impl<'a> Tree<'a> {
     }
 }
 
+impl<'a> ConvexHull<'a> {
+    /// Creates the convex hull of a point cloud.
+    ///
+    /// `tolerance` is the maximum distance a point can be moved to simplify the hull. Returns
+    /// `None` if the points are degenerate (fewer than four, or all on the same plane).
+    ///
+    /// ```
+    /// use newton::{ConvexHull, Newton};
+    ///
+    /// let newton = Newton::create();
+    ///
+    /// let points = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+    /// let tetrahedron = ConvexHull::create(&newton, &points, 0.0, None).unwrap();
+    ///
+    /// assert_eq!(4, tetrahedron.vertices().len());
+    /// assert_eq!(4, tetrahedron.faces().len());
+    /// ```
+    pub fn create(newton: &'a Newton,
+                  points: &[Vec3],
+                  tolerance: f32,
+                  offset: Option<Mat4>)
+                  -> Option<Self> {
+        unsafe {
+            let offset = mem::transmute(offset.as_ref());
+            let collision = ffi::NewtonCreateConvexHull(newton.as_raw(),
+                                                        points.len() as _,
+                                                        points.as_ptr() as *const f32,
+                                                        mem::size_of::<Vec3>() as _,
+                                                        tolerance,
+                                                        0,
+                                                        offset);
+            if collision.is_null() {
+                None
+            } else {
+                Some(Self::from_raw(collision, true))
+            }
+        }
+    }
+
+    /// Creates the convex hull of the vertices of a mesh.
+    pub fn from_mesh(newton: &'a Newton, mesh: &Mesh, tolerance: f32) -> Option<Self> {
+        unsafe {
+            let collision =
+                ffi::NewtonCreateConvexHullFromMesh(newton.as_raw(), mesh.as_raw(), tolerance, 0);
+            if collision.is_null() {
+                None
+            } else {
+                Some(Self::from_raw(collision, true))
+            }
+        }
+    }
+
+    /// Vertices of the hull, in local space.
+    pub fn vertices(&self) -> Vec<Vec3> {
+        unsafe {
+            let mut data: *mut f32 = std::ptr::null_mut();
+            let mut stride = 0;
+            let count = ffi::NewtonConvexHullGetVertexData(self.raw, &mut data, &mut stride);
+            let stride = stride as usize / mem::size_of::<f32>();
+
+            (0..count as usize).map(|i| {
+                                   let v = data.add(i * stride);
+                                   [*v, *v.add(1), *v.add(2)]
+                               })
+                               .collect()
+        }
+    }
+
+    /// Faces of the hull, as lists of indices into `vertices`.
+    pub fn faces(&self) -> Vec<Vec<u32>> {
+        unsafe {
+            let mut info: ffi::NewtonCollisionInfoRecord = mem::zeroed();
+            ffi::NewtonCollisionGetInfo(self.raw, &mut info);
+            let hull = info.__bindgen_anon_1.m_convexHull;
+
+            // a face can't have more vertices than the whole hull
+            let mut indices = vec![0; hull.m_vertexCount as usize];
+            (0..hull.m_faceCount).map(|face| {
+                                     let count = ffi::NewtonConvexHullGetFaceIndices(self.raw,
+                                                                                     face,
+                                                                                     indices.as_mut_ptr());
+                                     indices[..count as usize].iter().map(|&i| i as u32).collect()
+                                 })
+                                 .collect()
+        }
+    }
+}
+
 impl<'a> Null<'a> {
     pub fn create(newton: &'a Newton) -> Self {
         unsafe {
@@ -369,6 +617,17 @@ impl<'a> Cylinder<'a> {
     }
 }
 
+impl<'a> ChamferCylinder<'a> {
+    pub fn create(newton: &'a Newton, radius: f32, height: f32, offset: Option<Mat4>) -> Self {
+        unsafe {
+            let offset = mem::transmute(offset.as_ref());
+            let collision =
+                ffi::NewtonCreateChamferCylinder(newton.as_raw(), radius, height, 0, offset);
+            Self::from_raw(collision, true)
+        }
+    }
+}
+
 impl<'a> Capsule<'a> {
     pub fn create(newton: &'a Newton,
                   radius0: f32,
@@ -395,7 +654,8 @@ impl<'a> Capsule<'a> {
 // m_starInvertexDiagonals,
 /// HeightField grid construction pattern.
 #[repr(i32)]
-#[derive(Clone, Copy, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum HeightFieldGrid {
     NormalDiagonals = 0,
     InvertedDiagonals = 1,
@@ -407,12 +667,48 @@ pub enum HeightFieldGrid {
     StarInvertexDiagonals = 7,
 }
 
+impl HeightFieldGrid {
+    fn from_raw(raw: i32) -> Option<Self> {
+        match raw {
+            0 => Some(HeightFieldGrid::NormalDiagonals),
+            1 => Some(HeightFieldGrid::InvertedDiagonals),
+            2 => Some(HeightFieldGrid::AlternateOddRowsDiagonals),
+            3 => Some(HeightFieldGrid::AlternateEvenRowsDiagonals),
+            4 => Some(HeightFieldGrid::AlternateOddColumsDiagonals),
+            5 => Some(HeightFieldGrid::AlternateEvenColumsDiagonals),
+            6 => Some(HeightFieldGrid::StarDiagonals),
+            7 => Some(HeightFieldGrid::StarInvertexDiagonals),
+            _ => None,
+        }
+    }
+}
+
 impl<'a, T: Elevation> HeightField<'a, T> {
     /// A more convenient way to build a HeightField collision with default values.
-    pub fn builder() -> ! {
-        unimplemented!()
+    ///
+    /// ```
+    /// use newton::{HeightField, Newton};
+    ///
+    /// let newton = Newton::create();
+    ///
+    /// let elevation: Vec<f32> = (0..16 * 16).map(|i| (i % 16) as f32 * 0.1).collect();
+    /// let terrain = HeightField::builder(&newton, 16, 16).elevation(elevation)
+    ///                                                  .horizontal_scale(2.0, 2.0)
+    ///                                                  .build();
+    ///
+    /// assert_eq!((16, 16), (terrain.width(), terrain.height()));
+    /// assert_eq!(16 * 16, terrain.elevation().len());
+    /// ```
+    pub fn builder(newton: &'a Newton, width: usize, height: usize) -> HeightFieldBuilder<'a, T>
+        where T: Clone + Default
+    {
+        HeightFieldBuilder::new(newton, width, height)
     }
 
+    /// Creates a height field.
+    ///
+    /// ## Panics
+    /// Panics if `elevation` or `attrs` don't have `width * height` elements.
     pub fn create(newton: &'a Newton,
                   width: usize,
                   height: usize,
@@ -423,6 +719,9 @@ impl<'a, T: Elevation> HeightField<'a, T> {
                   hor_x_scale: f32,
                   hor_z_scale: f32)
                   -> Self {
+        assert_eq!(width * height, elevation.len(), "Wrong elevation length");
+        assert_eq!(width * height, attrs.len(), "Wrong attributes length");
+
         let data_type = T::newton_enum();
         unsafe {
             let raw = ffi::NewtonCreateHeightFieldCollision(newton.as_raw(),
@@ -440,9 +739,94 @@ impl<'a, T: Elevation> HeightField<'a, T> {
         }
     }
 
+    /// Displaces the grid vertices horizontally, for overhangs & cliffs.
+    ///
+    /// Newton doesn't report the displacement back, so it is missing from the `params` of
+    /// the collision.
+    ///
+    /// ```
+    /// use newton::prelude::*;
+    /// use newton::collision::{HeightFieldGrid, Params};
+    /// use newton::{Collision, HeightField, Newton};
+    ///
+    /// let newton = Newton::create();
+    ///
+    /// let terrain = HeightField::<f32>::builder(&newton, 4, 4)
+    ///     .grid(HeightFieldGrid::StarDiagonals)
+    ///     .elevation(vec![0.5; 16])
+    ///     .vertical_scale(2.0)
+    ///     .horizontal_displacement(vec![0; 16], 0.1)
+    ///     .build();
+    ///
+    /// let params = match terrain.params() {
+    ///     Params::HeightFieldF32(params) => params,
+    ///     _ => unreachable!(),
+    /// };
+    /// assert_eq!((4, 4), (params.width, params.height));
+    /// assert_eq!(HeightFieldGrid::StarDiagonals, params.grid);
+    /// assert_eq!(&[0.5; 16][..], &params.vertical_elevation[..]);
+    /// assert_eq!(2.0, params.vertical_scale);
+    /// assert!(params.horizontal_displacement.is_empty());
+    ///
+    /// // everything else round-trips
+    /// let params = Params::HeightFieldF32(params);
+    /// let copy = Collision::from_params(&newton, &params, None).unwrap();
+    /// assert_eq!(params, copy.params());
+    /// ```
+    ///
+    /// `map` holds one value per vertex, row by row. Each value packs the x & z offsets
+    /// as two `i8`, which are multiplied by `scale`.
     pub fn set_horizontal_displacement(&self, map: &[u16], scale: f32) {
+        assert_eq!(self.width() * self.height(), map.len(), "Wrong displacement length");
         unsafe { ffi::NewtonHeightFieldSetHorizontalDisplacement(self.raw, map.as_ptr(), scale) }
     }
+
+    fn info(&self) -> ffi::NewtonHeightFieldCollisionParam {
+        unsafe {
+            let mut info: ffi::NewtonCollisionInfoRecord = mem::zeroed();
+            ffi::NewtonCollisionGetInfo(self.raw, &mut info);
+            info.__bindgen_anon_1.m_heightField
+        }
+    }
+
+    /// Number of vertices along the x axis.
+    pub fn width(&self) -> usize {
+        self.info().m_width as _
+    }
+
+    /// Number of vertices along the z axis.
+    pub fn height(&self) -> usize {
+        self.info().m_height as _
+    }
+
+    pub fn grid(&self) -> HeightFieldGrid {
+        let grid = self.info().m_gridsDiagonals;
+        HeightFieldGrid::from_raw(grid as _).expect("Unknown height field grid")
+    }
+
+    /// Elevation of every vertex, row by row, before vertical scaling.
+    pub fn elevation(&self) -> &[T] {
+        let info = self.info();
+        let len = info.m_width as usize * info.m_height as usize;
+        unsafe { std::slice::from_raw_parts(info.m_vertialElevation as *const T, len) }
+    }
+
+    /// Attribute of every grid cell, reported as the face id in contacts.
+    pub fn attributes(&self) -> &[i8] {
+        let info = self.info();
+        let len = info.m_width as usize * info.m_height as usize;
+        unsafe { std::slice::from_raw_parts(info.m_atributes as *const i8, len) }
+    }
+
+    pub fn vertical_scale(&self) -> f32 {
+        self.info().m_verticalScale
+    }
+
+    /// Distance between grid vertices, along the x & z axes.
+    pub fn horizontal_scale(&self) -> (f32, f32) {
+        let info = self.info();
+        (info.m_horizonalScale_x, info.m_horizonalScale_z)
+    }
 }
 
 trait IntoCollision<'a> {
@@ -484,7 +868,7 @@ macro_rules! convex {
 }
 
 statik! {
-    Tree, Scene
+    Tree, Scene, UserMesh
 }
 
 impl<'a, T: Elevation> StaticShape for HeightField<'a, T> {}
@@ -526,6 +910,7 @@ pub fn intersection_test<A, B>(newton: &Newton,
 /// Closest point between two collisions.
 /// Type returned by the `closest_point` function.
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClosestPoint {
     pub p0: Vec3,
     pub mat0: Mat4,
@@ -567,6 +952,167 @@ pub fn closest_point<A, B>(newton: &Newton,
     }
 }
 
+/// Contact point between two collisions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Contact {
+    pub point: Vec3,
+    /// Contact normal, pointing from the second collision towards the first one.
+    pub normal: Vec3,
+    pub penetration: f32,
+    /// Attributes (face ids) of the faces in contact.
+    pub attribute_a: i64,
+    pub attribute_b: i64,
+}
+
+/// Transformation & velocities of a moving collision, for `collide_continue`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Motion {
+    pub matrix: Mat4,
+    pub velocity: Vec3,
+    pub omega: Vec3,
+}
+
+/// First contact between two moving collisions, returned by `collide_continue`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeOfImpact {
+    /// Time, from the start of the timestep, at which the collisions first touch.
+    pub time: Duration,
+    pub contacts: Vec<Contact>,
+}
+
+// Newton returns contact points & normals as packed triplets.
+struct ContactBuffers {
+    points: Vec<f32>,
+    normals: Vec<f32>,
+    penetrations: Vec<f32>,
+    attributes_a: Vec<i64>,
+    attributes_b: Vec<i64>,
+}
+
+impl ContactBuffers {
+    fn new(max_contacts: usize) -> Self {
+        Self { points: vec![0.0; max_contacts * 3],
+               normals: vec![0.0; max_contacts * 3],
+               penetrations: vec![0.0; max_contacts],
+               attributes_a: vec![0; max_contacts],
+               attributes_b: vec![0; max_contacts] }
+    }
+
+    fn contacts(&self, count: usize) -> Vec<Contact> {
+        (0..count).map(|i| {
+                      let p = &self.points[i * 3..i * 3 + 3];
+                      let n = &self.normals[i * 3..i * 3 + 3];
+                      Contact { point: [p[0], p[1], p[2]],
+                                normal: [n[0], n[1], n[2]],
+                                penetration: self.penetrations[i],
+                                attribute_a: self.attributes_a[i] as _,
+                                attribute_b: self.attributes_b[i] as _ }
+                  })
+                  .collect()
+    }
+}
+
+/// Computes up to `max_contacts` contact points between two transformed collisions.
+///
+/// Returns an empty vector if the collisions don't intersect.
+pub fn collide<A, B>(newton: &Newton,
+                     col_a: &A,
+                     mat_a: Mat4,
+                     col_b: &B,
+                     mat_b: Mat4,
+                     max_contacts: usize,
+                     thread_idx: usize)
+                     -> Vec<Contact>
+    where A: NewtonCollision,
+          B: NewtonCollision
+{
+    let mut buffers = ContactBuffers::new(max_contacts);
+    let count = unsafe {
+        ffi::NewtonCollisionCollide(newton.as_raw(),
+                                    max_contacts as _,
+                                    col_a.as_raw(),
+                                    mat_a[0].as_ptr(),
+                                    col_b.as_raw(),
+                                    mat_b[0].as_ptr(),
+                                    buffers.points.as_mut_ptr(),
+                                    buffers.normals.as_mut_ptr(),
+                                    buffers.penetrations.as_mut_ptr(),
+                                    buffers.attributes_a.as_mut_ptr() as _,
+                                    buffers.attributes_b.as_mut_ptr() as _,
+                                    thread_idx as _)
+    };
+    buffers.contacts(count as usize)
+}
+
+/// Computes the time of impact, and the contacts at that time, between two convex
+/// collisions moving at constant velocity during `timestep`.
+///
+/// Returns `None` if they don't collide within the timestep.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use newton::collision::{collide_continue, Motion};
+/// use newton::{Newton, Sphere};
+///
+/// let newton = Newton::create();
+/// let sphere = Sphere::create(&newton, 0.5, None);
+///
+/// let identity = [[1.0, 0.0, 0.0, 0.0], [0.0, 1.0, 0.0, 0.0], [0.0, 0.0, 1.0, 0.0], [0.0, 0.0, 0.0, 1.0]];
+/// let mut matrix = identity;
+/// matrix[3] = [4.0, 0.0, 0.0, 1.0];
+///
+/// // sword swing
+/// let blade = Motion { matrix: identity, velocity: [10.0, 0.0, 0.0], omega: [0.0; 3] };
+/// let target = Motion { matrix, velocity: [0.0; 3], omega: [0.0; 3] };
+///
+/// let step = Duration::from_millis(500);
+/// let hit = collide_continue(&newton, step, &sphere, blade, &sphere, target, 4, 0).unwrap();
+/// assert!(hit.time < step);
+/// ```
+pub fn collide_continue<A, B>(newton: &Newton,
+                              timestep: Duration,
+                              col_a: &A,
+                              motion_a: Motion,
+                              col_b: &B,
+                              motion_b: Motion,
+                              max_contacts: usize,
+                              thread_idx: usize)
+                              -> Option<TimeOfImpact>
+    where A: ConvexShape,
+          B: ConvexShape
+{
+    let mut buffers = ContactBuffers::new(max_contacts);
+    let mut time = 0.0;
+    let count = unsafe {
+        ffi::NewtonCollisionCollideContinue(newton.as_raw(),
+                                            max_contacts as _,
+                                            timestep.as_secs_f32(),
+                                            col_a.as_raw(),
+                                            motion_a.matrix[0].as_ptr(),
+                                            motion_a.velocity.as_ptr(),
+                                            motion_a.omega.as_ptr(),
+                                            col_b.as_raw(),
+                                            motion_b.matrix[0].as_ptr(),
+                                            motion_b.velocity.as_ptr(),
+                                            motion_b.omega.as_ptr(),
+                                            &mut time,
+                                            buffers.points.as_mut_ptr(),
+                                            buffers.normals.as_mut_ptr(),
+                                            buffers.penetrations.as_mut_ptr(),
+                                            buffers.attributes_a.as_mut_ptr() as _,
+                                            buffers.attributes_b.as_mut_ptr() as _,
+                                            thread_idx as _)
+    };
+    if count == 0 {
+        return None;
+    }
+    Some(TimeOfImpact { time: Duration::from_secs_f32(time.max(0.0)),
+                        contacts: buffers.contacts(count as usize) })
+}
+
 /// NewtonCollision functions.
 pub trait NewtonCollision {
     fn as_raw(&self) -> *const ffi::NewtonCollision;
@@ -589,8 +1135,61 @@ pub trait NewtonCollision {
         }
     }
 
+    /// Reads the shape parameters.
     fn params(&self) -> Params {
-        unimplemented!()
+        unsafe {
+            let mut info: ffi::NewtonCollisionInfoRecord = mem::zeroed();
+            ffi::NewtonCollisionGetInfo(self.as_raw(), &mut info);
+            let info = &info.__bindgen_anon_1;
+
+            match Collision::from_raw(self.as_raw(), false) {
+                Collision::Cuboid(_) => {
+                    let p = info.m_box;
+                    Params::Cuboid { dx: p.m_x, dy: p.m_y, dz: p.m_z }
+                }
+                Collision::Sphere(_) => Params::Sphere { radius: info.m_sphere.m_radio },
+                Collision::Cylinder(_) => {
+                    let p = info.m_cylinder;
+                    Params::Cylinder { radius0: p.m_radio0, radius1: p.m_radio1, height: p.m_height }
+                }
+                Collision::ChamferCylinder(_) => {
+                    let p = info.m_chamferCylinder;
+                    Params::ChamferCylinder { radius: p.m_radio, height: p.m_height }
+                }
+                Collision::Capsule(_) => {
+                    let p = info.m_capsule;
+                    Params::Capsule { radius0: p.m_radio0, radius1: p.m_radio1, height: p.m_height }
+                }
+                Collision::Cone(_) => {
+                    let p = info.m_cone;
+                    Params::Cone { radius: p.m_radio, height: p.m_height }
+                }
+                Collision::Compound(_) => {
+                    Params::Compound { children_count: info.m_compoundCollision.m_chidrenCount as _ }
+                }
+                Collision::Tree(_) => {
+                    let p = info.m_collisionTree;
+                    Params::Tree { vertex_count: p.m_vertexCount as _, index_count: p.m_indexCount as _ }
+                }
+                Collision::Scene(_) => Params::Scene {
+                    children_proxy_count: info.m_sceneCollision.m_childrenProxyCount as _,
+                },
+                Collision::UserMesh(_) => Params::UserMesh {},
+                Collision::FracturedCompound(_) => Params::FracturedCompound {},
+                Collision::ConvexHull(ref hull) => {
+                    Params::ConvexHull { vertices: hull.vertices().into(), tolerance: 0.0 }
+                }
+                Collision::Null(_) => Params::Null {},
+                Collision::DeformableSolid(_) => Params::DeformableSolid {},
+                Collision::MassSpringDamperSystem(_) => Params::MassSpringDamperSystem {},
+                Collision::HeightFieldF32(_) => {
+                    Params::HeightFieldF32(height_field_params(&info.m_heightField))
+                }
+                Collision::HeightFieldU16(_) => {
+                    Params::HeightFieldU16(height_field_params(&info.m_heightField))
+                }
+            }
+        }
     }
 
     fn for_each_polygon<F>(&self, matrix: Mat4, mut callback: F)
@@ -650,4 +1249,165 @@ pub trait NewtonCollision {
     fn user_id(&self) -> u32 {
         unsafe { ffi::NewtonCollisionGetUserID(self.as_raw()) }
     }
+
+    /// Casts a ray from `p0` to `p1` against the shape, placed with `matrix`.
+    ///
+    /// Points & the returned normal are in world space. The matrix must be a rigid
+    /// transformation (use `set_scale` to scale the shape).
+    ///
+    /// ```
+    /// use newton::prelude::*;
+    /// use newton::{Newton, Sphere};
+    ///
+    /// let newton = Newton::create();
+    /// let sphere = Sphere::create(&newton, 1.0, None);
+    ///
+    /// let mut matrix = [[1.0, 0.0, 0.0, 0.0],
+    ///                   [0.0, 1.0, 0.0, 0.0],
+    ///                   [0.0, 0.0, 1.0, 0.0],
+    ///                   [0.0, 0.0, 0.0, 1.0]];
+    /// matrix[3] = [0.0, 0.0, 5.0, 1.0];
+    ///
+    /// let hit = sphere.ray_cast(matrix, [0.0, 0.0, 0.0], [0.0, 0.0, 10.0]).unwrap();
+    /// assert!((hit.t - 0.4).abs() < 1e-4);
+    ///
+    /// assert!(sphere.ray_cast(matrix, [0.0, 2.0, 0.0], [0.0, 2.0, 10.0]).is_none());
+    /// ```
+    fn ray_cast(&self, matrix: Mat4, p0: Vec3, p1: Vec3) -> Option<ShapeRayHit> {
+        let local0 = untransform(&matrix, p0);
+        let local1 = untransform(&matrix, p1);
+        let mut normal = [0.0; 4];
+        let mut attribute = 0;
+        let t = unsafe {
+            ffi::NewtonCollisionRayCast(self.as_raw(),
+                                        local0.as_ptr(),
+                                        local1.as_ptr(),
+                                        normal.as_mut_ptr(),
+                                        &mut attribute)
+        };
+        if t < 0.0 || t > 1.0 {
+            return None;
+        }
+        let [nx, ny, nz, _] = normal;
+        Some(ShapeRayHit { t, normal: rotate(&matrix, [nx, ny, nz]), attribute: attribute as _ })
+    }
+
+    /// Farthest point of the shape along `dir`, in local space.
+    fn support_vertex(&self, dir: Vec3) -> Vec3 {
+        let mut vertex = [0.0; 4];
+        unsafe { ffi::NewtonCollisionSupportVertex(self.as_raw(), dir.as_ptr(), vertex.as_mut_ptr()) }
+        let [x, y, z, _] = vertex;
+        [x, y, z]
+    }
+
+    /// World space AABB of the shape, placed with `matrix`.
+    fn calculate_aabb(&self, matrix: Mat4) -> (Vec3, Vec3) {
+        let mut min = [0.0; 4];
+        let mut max = [0.0; 4];
+        unsafe {
+            ffi::NewtonCollisionCalculateAABB(self.as_raw(),
+                                              matrix[0].as_ptr(),
+                                              min.as_mut_ptr(),
+                                              max.as_mut_ptr())
+        }
+        ([min[0], min[1], min[2]], [max[0], max[1], max[2]])
+    }
+
+    /// Volume of the shape. Zero for non-convex shapes other than `Compound`.
+    fn volume(&self) -> f32 {
+        match unsafe { Collision::from_raw(self.as_raw(), false) } {
+            Collision::Compound(compound) => compound.collisions().map(|c| c.volume()).sum(),
+            _ => unsafe { ffi::NewtonConvexCollisionCalculateVolume(self.as_raw()) },
+        }
+    }
+
+    /// Diagonal of the inertia matrix of a unit mass with this shape, about its centre of mass.
+    ///
+    /// Newton computes it for unit mass, so it is also defined for shapes without volume.
+    fn inertia_matrix(&self) -> Vec3 {
+        match unsafe { Collision::from_raw(self.as_raw(), false) } {
+            // children are weighted by their volume
+            Collision::Compound(compound) => match compound.volume() {
+                volume if volume > 0.0 => compound.mass_properties(1.0 / volume).inertia,
+                _ => [0.0, 0.0, 0.0],
+            },
+            _ => unsafe {
+                let mut inertia = [0.0, 0.0, 0.0];
+                let mut origin = [0.0, 0.0, 0.0];
+                ffi::NewtonConvexCollisionCalculateInertialMatrix(self.as_raw(),
+                                                                  inertia.as_mut_ptr(),
+                                                                  origin.as_mut_ptr());
+                inertia
+            },
+        }
+    }
+
+    /// Centre of mass of the shape, assuming uniform density.
+    fn centre_of_mass(&self) -> Vec3 {
+        self.mass_properties(1.0).centre_of_mass
+    }
+
+    /// Mass properties of the shape, for the given density (kg/m³, if your units are meters).
+    ///
+    /// Children of compounds all get the same density. Use `Compound::mass_properties_with`
+    /// to give them different densities.
+    fn mass_properties(&self, density: f32) -> MassProperties {
+        match unsafe { Collision::from_raw(self.as_raw(), false) } {
+            Collision::Compound(compound) => compound.mass_properties_with(|_, _| density),
+            _ => unsafe {
+                let volume = ffi::NewtonConvexCollisionCalculateVolume(self.as_raw());
+                let mut inertia = [0.0, 0.0, 0.0];
+                let mut origin = [0.0, 0.0, 0.0];
+                ffi::NewtonConvexCollisionCalculateInertialMatrix(self.as_raw(),
+                                                                  inertia.as_mut_ptr(),
+                                                                  origin.as_mut_ptr());
+                let mass = density * volume;
+                let [ix, iy, iz] = inertia;
+                MassProperties { mass,
+                                 inertia: [ix * mass, iy * mass, iz * mass],
+                                 centre_of_mass: origin }
+            },
+        }
+    }
+}
+
+// The returned slices point to the data owned by the collision.
+unsafe fn height_field_params<'a, T: Elevation + Clone>(info: &ffi::NewtonHeightFieldCollisionParam)
+                                                        -> HeightFieldParams<'a, T> {
+    let len = info.m_width as usize * info.m_height as usize;
+    let elevation = std::slice::from_raw_parts(info.m_vertialElevation as *const T, len);
+    let attributes = std::slice::from_raw_parts(info.m_atributes as *const i8, len);
+    HeightFieldParams { width: info.m_width as _,
+                        height: info.m_height as _,
+                        grid: HeightFieldGrid::from_raw(info.m_gridsDiagonals as _)
+                            .expect("Unknown height field grid"),
+                        vertical_elevation: Cow::Borrowed(elevation),
+                        vertical_scale: info.m_verticalScale,
+                        horizontal_scale_x: info.m_horizonalScale_x,
+                        horizontal_scale_z: info.m_horizonalScale_z,
+                        // not reported by NewtonCollisionGetInfo
+                        horizontal_displacement_scale: 0.0,
+                        horizontal_displacement: Cow::Borrowed(&[]),
+                        attributes: Cow::Borrowed(attributes) }
+}
+
+/// Intersection of a ray with a single shape, returned by `NewtonCollision::ray_cast`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ShapeRayHit {
+    /// Position of the hit along the ray, between 0 (`p0`) and 1 (`p1`).
+    pub t: f32,
+    pub normal: Vec3,
+    /// Attribute (face id) of the face that was hit.
+    pub attribute: i64,
+}
+
+/// Mass, inertia and centre of mass of a collision shape.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MassProperties {
+    pub mass: f32,
+    /// Diagonal of the inertia matrix, about the centre of mass.
+    pub inertia: Vec3,
+    /// Centre of mass, in local space.
+    pub centre_of_mass: Vec3,
 }
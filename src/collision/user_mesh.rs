@@ -0,0 +1,260 @@
+use std::cell::RefCell;
+use std::mem;
+use std::os::raw::{c_int, c_void};
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::ffi;
+use crate::math::{cross, length, normalize, sub, Vec3};
+use crate::newton::Newton;
+
+use super::UserMesh;
+
+/// Geometry & queries of a `UserMesh` collision, implemented by the application.
+///
+/// Everything is expressed in the local space of the collision. Methods may be called
+/// from several of Newton's worker threads at once. Panics can't unwind into Newton, so
+/// they are caught, and the query is treated as empty (no faces, no ray hit).
+pub trait UserMeshShape: Send + Sync + 'static {
+    /// Bounds of the whole mesh.
+    fn aabb(&self) -> (Vec3, Vec3);
+
+    /// Adds the faces that overlap the given box to `faces`.
+    fn collide(&self, min: Vec3, max: Vec3, faces: &mut PolygonSoup);
+
+    /// Casts a ray from `p0` to `p1`. Rays that don't hit the mesh are ignored if this is
+    /// not implemented.
+    fn ray_cast(&self, p0: Vec3, p1: Vec3) -> Option<MeshRayHit> {
+        let _ = (p0, p1);
+        None
+    }
+
+    /// Called when Newton destroys the collision, right before the shape is dropped.
+    fn destroy(&mut self) {}
+}
+
+/// Intersection of a ray with a `UserMeshShape`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MeshRayHit {
+    /// Position of the hit along the ray, between 0 (`p0`) and 1 (`p1`).
+    pub t: f32,
+    pub normal: Vec3,
+    /// Id of the face that was hit.
+    pub face_id: i32,
+}
+
+/// Faces collected by `UserMeshShape::collide`, in the layout Newton expects.
+#[derive(Debug, Default)]
+pub struct PolygonSoup {
+    vertices: Vec<Vec3>,
+    face_counts: Vec<c_int>,
+    indices: Vec<c_int>,
+}
+
+impl PolygonSoup {
+    /// Adds a convex, planar face, with its vertices in counter-clockwise order.
+    /// `face_id` is reported back in contacts & ray casts.
+    ///
+    /// Faces with less than three vertices are ignored.
+    pub fn add_face(&mut self, vertices: &[Vec3], face_id: i32) {
+        let count = vertices.len();
+        if count < 3 {
+            return;
+        }
+
+        let first = self.vertices.len() as c_int;
+        self.vertices.extend_from_slice(vertices);
+
+        // face normal (Newell's method) is stored along the vertices
+        let mut normal = [0.0, 0.0, 0.0];
+        for (i, a) in vertices.iter().enumerate() {
+            let b = vertices[(i + 1) % count];
+            let n = cross(*a, b);
+            for j in 0..3 {
+                normal[j] += n[j];
+            }
+        }
+        let normal_idx = self.vertices.len() as c_int;
+        self.vertices.push(normalize(normal));
+
+        let size = vertices.iter()
+                           .skip(1)
+                           .map(|v| length(sub(*v, vertices[0])))
+                           .fold(0.0f32, f32::max);
+
+        // vertex indices, face id, normal, adjacent face normals (unknown, so the face
+        // normal is used for every edge), face size
+        self.indices.extend((0..count as c_int).map(|i| first + i));
+        self.indices.push(face_id);
+        self.indices.push(normal_idx);
+        self.indices.extend((0..count).map(|_| normal_idx));
+        self.indices.push(size.ceil().max(1.0) as c_int);
+        self.face_counts.push(count as c_int);
+    }
+
+    pub fn len(&self) -> usize {
+        self.face_counts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.face_counts.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+        self.face_counts.clear();
+        self.indices.clear();
+    }
+}
+
+thread_local! {
+    // Newton reads the faces after the collide callback returns, so they are kept in a
+    // buffer per thread until the next call on the same thread.
+    static FACES: RefCell<PolygonSoup> = RefCell::new(PolygonSoup::default());
+}
+
+impl<'a> UserMesh<'a> {
+    /// Creates a collision whose geometry is provided by `shape` on demand.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use newton::collision::user_mesh::{MeshRayHit, PolygonSoup, UserMeshShape};
+    /// use newton::math::Vec3;
+    /// use newton::newton::ray_cast::ClosestHit;
+    /// use newton::prelude::*;
+    /// use newton::{DynamicBody, Newton, Sphere, UserMesh};
+    ///
+    /// // flat ground, split in unit quads
+    /// struct Ground;
+    ///
+    /// impl UserMeshShape for Ground {
+    ///     fn aabb(&self) -> (Vec3, Vec3) {
+    ///         ([-1e3, -1.0, -1e3], [1e3, 0.0, 1e3])
+    ///     }
+    ///
+    ///     fn ray_cast(&self, p0: Vec3, p1: Vec3) -> Option<MeshRayHit> {
+    ///         // only rays crossing the plane downwards
+    ///         if p0[1] < 0.0 || p1[1] > 0.0 {
+    ///             return None;
+    ///         }
+    ///         let t = p0[1] / (p0[1] - p1[1]);
+    ///         Some(MeshRayHit { t, normal: [0.0, 1.0, 0.0], face_id: 0 })
+    ///     }
+    ///
+    ///     fn collide(&self, min: Vec3, max: Vec3, faces: &mut PolygonSoup) {
+    ///         if min[1] > 0.0 {
+    ///             return;
+    ///         }
+    ///         for x in min[0].floor() as i32..max[0].ceil() as i32 {
+    ///             for z in min[2].floor() as i32..max[2].ceil() as i32 {
+    ///                 let (x, z) = (x as f32, z as f32);
+    ///                 let quad = [[x, 0.0, z], [x, 0.0, z + 1.0], [x + 1.0, 0.0, z + 1.0], [x + 1.0, 0.0, z]];
+    ///                 faces.add_face(&quad, 0);
+    ///             }
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let mut newton = Newton::config().gravity([0.0, -9.8, 0.0]).build();
+    /// let ball = {
+    ///     let ground = UserMesh::create(&newton, Ground);
+    ///     DynamicBody::builder(&newton, &ground).into_handle();
+    ///
+    ///     let sphere = Sphere::create(&newton, 0.5, None);
+    ///     DynamicBody::builder(&newton, &sphere).position([0.5, 2.0, 0.5]).mass(1.0).into_handle()
+    /// };
+    ///
+    /// let hit = newton.ray_cast::<ClosestHit>([5.5, 5.0, 5.5], [5.5, -5.0, 5.5], ()).unwrap();
+    /// assert!(hit.position[1].abs() < 1e-3);
+    /// assert!((hit.normal[1] - 1.0).abs() < 1e-3);
+    ///
+    /// // the ball falls and rests on the ground
+    /// for _ in 0..120 {
+    ///     newton.update(Duration::new(0, 1_000_000_000 / 60));
+    /// }
+    /// let [_, y, _] = newton.storage().body(ball).unwrap().position();
+    /// assert!(y > 0.4 && y < 0.6);
+    /// ```
+    pub fn create<S: UserMeshShape>(newton: &'a Newton, shape: S) -> Self {
+        let (min, max) = shape.aabb();
+        let shape: Box<Box<dyn UserMeshShape>> = Box::new(Box::new(shape));
+        unsafe {
+            let collision = ffi::NewtonCreateUserMeshCollision(newton.as_raw(),
+                                                               min.as_ptr(),
+                                                               max.as_ptr(),
+                                                               Box::into_raw(shape) as _,
+                                                               Some(collide),
+                                                               Some(ray_hit),
+                                                               Some(destroy),
+                                                               None,
+                                                               Some(aabb_test),
+                                                               None,
+                                                               None,
+                                                               0);
+            Self::from_raw(collision, true)
+        }
+    }
+}
+
+unsafe fn shape<'a>(udata: *const c_void) -> &'a dyn UserMeshShape {
+    &**(udata as *const Box<dyn UserMeshShape>)
+}
+
+unsafe extern "C" fn collide(desc: *mut ffi::NewtonUserMeshCollisionCollideDesc,
+                             _: *const c_void) {
+    let desc = &mut *desc;
+    let shape = shape(desc.m_userData);
+    let [x0, y0, z0, _] = desc.m_boxP0;
+    let [x1, y1, z1, _] = desc.m_boxP1;
+
+    FACES.with(|faces| {
+             let mut faces = faces.borrow_mut();
+             faces.clear();
+             let collide = || shape.collide([x0, y0, z0], [x1, y1, z1], &mut faces);
+             if panic::catch_unwind(AssertUnwindSafe(collide)).is_err() {
+                 // faces added before the panic are discarded
+                 faces.clear();
+             }
+
+             desc.m_faceCount = faces.face_counts.len() as _;
+             desc.m_vertexStrideInBytes = mem::size_of::<Vec3>() as _;
+             desc.m_vertex = faces.vertices.as_ptr() as *mut f32;
+             desc.m_faceIndexCount = faces.face_counts.as_ptr() as *mut c_int;
+             desc.m_faceVertexIndex = faces.indices.as_ptr() as *mut c_int;
+         });
+}
+
+unsafe extern "C" fn ray_hit(desc: *mut ffi::NewtonUserMeshCollisionRayHitDesc) -> f32 {
+    let desc = &mut *desc;
+    let [x0, y0, z0, _] = desc.m_p0;
+    let [x1, y1, z1, _] = desc.m_p1;
+
+    let shape = shape(desc.m_userData);
+    let hit = panic::catch_unwind(AssertUnwindSafe(|| shape.ray_cast([x0, y0, z0], [x1, y1, z1])));
+    match hit.unwrap_or(None) {
+        Some(hit) => {
+            let [nx, ny, nz] = hit.normal;
+            desc.m_normalOut = [nx, ny, nz, 0.0];
+            desc.m_userIdOut = hit.face_id as _;
+            hit.t
+        }
+        // anything above 1 means no intersection
+        None => 1.2,
+    }
+}
+
+unsafe extern "C" fn aabb_test(udata: *mut c_void, p0: *const f32, p1: *const f32) -> c_int {
+    let (min, max) = match panic::catch_unwind(AssertUnwindSafe(|| shape(udata).aabb())) {
+        Ok(aabb) => aabb,
+        Err(_) => return 0,
+    };
+    let p0 = std::slice::from_raw_parts(p0, 3);
+    let p1 = std::slice::from_raw_parts(p1, 3);
+    (0..3).all(|i| p0[i] <= max[i] && p1[i] >= min[i]) as c_int
+}
+
+unsafe extern "C" fn destroy(udata: *mut c_void) {
+    let mut shape = Box::from_raw(udata as *mut Box<dyn UserMeshShape>);
+    // the shape is dropped within the closure, so panics in Drop are caught too
+    let _ = panic::catch_unwind(AssertUnwindSafe(move || shape.destroy()));
+}
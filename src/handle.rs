@@ -1,6 +1,6 @@
 //! ```
 //! use newton::prelude::*;
-//! use newton::handle::Handle;
+//! use newton::handle::CollisionHandle;
 //! use newton::{Newton, Sphere, Collision};
 //!
 //! let newton = Newton::create();
@@ -9,15 +9,20 @@
 //!
 //! sphere.set_user_id(42);
 //!
-//! let sphere = sphere.into_handle(&newton);
+//! let sphere: CollisionHandle = sphere.into_handle(&newton);
 //!
 //! let col = newton.storage().collision(sphere);
 //!
 //! assert_eq!(Some(42), col.map(|b| b.user_id()));
-//! assert_eq!(None, newton.storage().collision(Handle::null()));
+//! assert_eq!(None, newton.storage().collision(CollisionHandle::null()));
 //! ```
 use crate::newton::Newton;
 
+/// Untyped handle.
+///
+/// Bodies, collisions, and joints are referenced by their own typed handles
+/// (`BodyHandle`, `CollisionHandle`, and `JointHandle`). Use this type when you
+/// need to mix handles of different kinds, or to reference compound & scene nodes.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
 pub struct Handle(HandleInner);
 
@@ -48,23 +53,140 @@ pub(crate) enum HandleInner {
 unsafe impl Send for Handle {}
 unsafe impl Sync for Handle {}
 
+// Pointers are serialized as plain integers. A deserialized handle is only meaningful
+// for the Newton world that produced it, but looking it up elsewhere is safe, because
+// storages check that the handle is present before touching the pointer.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename = "Handle")]
+enum HandleRepr {
+    Pointer(usize),
+    Index(usize),
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Handle {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let repr = match self.inner() {
+            HandleInner::Pointer(ptr) => HandleRepr::Pointer(ptr as usize),
+            HandleInner::Index(idx) => HandleRepr::Index(idx),
+        };
+        repr.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Handle {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match HandleRepr::deserialize(deserializer)? {
+            HandleRepr::Pointer(ptr) => Handle::from_ptr(ptr as _),
+            HandleRepr::Index(idx) => Handle::from_usize(idx),
+        })
+    }
+}
+
+macro_rules! typed_handles {
+    ($( $(#[$($meta:meta)+])* pub struct $handle:ident; )*) => {$(
+        $(#[$($meta)+])*
+        #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        pub struct $handle(Handle);
+
+        impl $handle {
+            pub fn null() -> Self {
+                Self(Handle::null())
+            }
+
+            /// Returns the untyped handle.
+            pub fn into_untyped(self) -> Handle {
+                self.0
+            }
+
+            /// Wraps an untyped handle, such as one returned by `into_untyped`.
+            ///
+            /// The kind of the handle is not checked. Handles of anything else (compound
+            /// or scene nodes, other kinds of objects) are never found in the storage.
+            pub fn from_untyped(handle: Handle) -> Self {
+                Self(handle)
+            }
+
+            pub(crate) fn from_ptr(ptr: *const ()) -> Self {
+                Self(Handle::from_ptr(ptr))
+            }
+
+            pub(crate) fn inner(&self) -> HandleInner {
+                self.0.inner()
+            }
+        }
+
+        impl From<$handle> for Handle {
+            fn from(handle: $handle) -> Self {
+                handle.0
+            }
+        }
+    )*}
+}
+
+typed_handles! {
+    /// Handle to a body stored in a `NewtonStorage`.
+    pub struct BodyHandle;
+
+    /// Handle to a collision stored in a `NewtonStorage`.
+    pub struct CollisionHandle;
+
+    /// Handle to a joint stored in a `NewtonStorage`.
+    pub struct JointHandle;
+}
+
+/// Handle to a force field registered with `Newton::add_force_field`.
+///
+/// Force fields are stored by index, so unlike the other typed handles this one can't
+/// be converted into an untyped `Handle`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ForceFieldHandle(usize);
+
+impl ForceFieldHandle {
+    /// Returns a handle that never refers to a force field.
+    pub fn null() -> Self {
+        Self(usize::max_value())
+    }
+
+    pub(crate) fn from_index(idx: usize) -> Self {
+        Self(idx)
+    }
+
+    pub(crate) fn index(&self) -> usize {
+        self.0
+    }
+}
+
 pub trait FromHandle<'a>: Sized {
+    /// Type of handle the object is borrowed with.
+    type Handle;
+
     /// Borrows object from newton storage.
-    fn from_handle(newton: &'a Newton, handle: Handle) -> Option<Self>;
+    fn from_handle(newton: &'a Newton, handle: Self::Handle) -> Option<Self>;
 
     /// Retakes ownership of an object stored in Newton.
     /// The returned object is destroyed after drop.
-    fn from_handle_owned(newton: &'a mut Newton, handle: Handle) -> Option<Self>;
+    fn from_handle_owned(newton: &'a mut Newton, handle: Self::Handle) -> Option<Self>;
 }
 
 pub trait IntoHandle {
+    /// Type of handle returned by `into_handle`.
+    type Handle;
+
     /// Moves the object into the given Newton and returns a handle to
     /// borrow it or retake ownership of it later.
-    fn into_handle(self, newton: &Newton) -> Handle;
+    fn into_handle(self, newton: &Newton) -> Self::Handle;
 }
 
 pub trait AsHandle {
+    /// Type of handle returned by `as_handle`.
+    type Handle;
+
     /// Returns the same vale that would be returned by `into_handle`, but
     /// without moving the object.
-    fn as_handle(&self, newton: &Newton) -> Handle;
+    fn as_handle(&self, newton: &Newton) -> Self::Handle;
 }
@@ -4,6 +4,7 @@
 //!
 //! [wiki]: http://www.newtondynamics.com/wiki/index.php5?title=Category:Joint_functions
 //!
+use std::borrow::Cow;
 use std::marker::PhantomData;
 use std::mem;
 use std::ptr;
@@ -11,7 +12,7 @@ use std::time::Duration;
 
 use crate::body::{Body, NewtonBody};
 use crate::ffi;
-use crate::handle::{AsHandle, Handle, IntoHandle};
+use crate::handle::{AsHandle, FromHandle, IntoHandle, JointHandle};
 use crate::math::Vec3;
 use crate::newton::Newton;
 
@@ -37,7 +38,7 @@ struct UserData {
     world: *const ffi::NewtonWorld,
 
     /// Human readable name (useful for debugging)
-    name: Option<&'static str>,
+    name: Option<Cow<'static, str>>,
 
     /// Ball joint callback
     ball_callback: Option<Box<dyn FnMut(Ball, Duration)>>,
@@ -113,24 +114,25 @@ impl<'a> Ball<'a> {
         }
     }
 
-    pub fn create<'b, 'c, 'd, B, C>(newton: &'d Newton,
-                                    pivot: Vec3,
-                                    child: &'b B,
-                                    parent: Option<&'c C>,
-                                    name: Option<&'static str>)
-                                    -> Self
+    pub fn create<'b, 'c, 'd, B, C, N>(newton: &'d Newton,
+                                       pivot: Vec3,
+                                       child: &'b B,
+                                       parent: Option<&'c C>,
+                                       name: Option<N>)
+                                       -> Self
         where 'b: 'a,
               'c: 'a,
               'd: 'b + 'c,
               B: NewtonBody,
-              C: NewtonBody
+              C: NewtonBody,
+              N: Into<Cow<'static, str>>
     {
         unsafe {
             let world = newton.as_raw();
             let child = child.as_raw();
             let parent = parent.map(|b| b.as_raw()).unwrap_or(ptr::null());
             let raw = ffi::NewtonConstraintCreateBall(world, pivot.as_ptr(), child, parent);
-            let udata = UserData { name,
+            let udata = UserData { name: name.map(Into::into),
                                    joint_type: Type::Ball,
                                    world: newton.as_raw(),
                                    ball_callback: None,
@@ -170,18 +172,19 @@ impl<'a> Ball<'a> {
 impl<'a> Slider<'a> {
     // Example playground with lifetimes and lifetime bounds:
     // https://play.rust-lang.org/?version=stable&mode=debug&edition=2018&gist=e1ec143dee1f116fa106ff7f6bfbb3de
-    pub fn create<'b, 'c, 'd, B, C>(newton: &'d Newton,
-                                    pivot: Vec3,
-                                    pin_dir: Vec3,
-                                    child: &'b B,
-                                    parent: Option<&'c C>,
-                                    name: Option<&'static str>)
-                                    -> Self
+    pub fn create<'b, 'c, 'd, B, C, N>(newton: &'d Newton,
+                                       pivot: Vec3,
+                                       pin_dir: Vec3,
+                                       child: &'b B,
+                                       parent: Option<&'c C>,
+                                       name: Option<N>)
+                                       -> Self
         where 'b: 'a,
               'c: 'a,
               'd: 'b + 'c,
               B: NewtonBody,
-              C: NewtonBody
+              C: NewtonBody,
+              N: Into<Cow<'static, str>>
     {
         unsafe {
             let world = newton.as_raw();
@@ -192,7 +195,7 @@ impl<'a> Slider<'a> {
                                                         pin_dir.as_ptr(),
                                                         child,
                                                         parent);
-            let udata = UserData { name,
+            let udata = UserData { name: name.map(Into::into),
                                    joint_type: Type::Slider,
                                    world: newton.as_raw(),
                                    ball_callback: None,
@@ -208,6 +211,12 @@ impl<'a> Slider<'a> {
 unsafe extern "C" fn joint_destroy(me: *const ffi::NewtonJoint) {
     let udata = ffi::NewtonJointGetUserData(me);
     let mut udata: Box<UserData> = Box::from_raw(udata as _);
+
+    // The joint may be destroyed by Newton (along with one of its bodies) while its handle
+    // is still in the storage. The world user data is gone when the world itself is dropped.
+    if !ffi::NewtonWorldGetUserData(udata.world).is_null() {
+        Newton::storage_from_world(udata.world).forget_constraint(JointHandle::from_ptr(me as _));
+    }
     if let Some(mut destroy) = udata.destroy_callback.take() {
         destroy()
     }
@@ -223,22 +232,34 @@ pub trait NewtonJoint {
         self.as_raw()
     }
 
-    fn name(&self) -> Option<&'static str> {
+    /// Returns a copy of the name of the joint. Static names are not reallocated.
+    fn name(&self) -> Option<Cow<'static, str>> {
         unsafe {
-            let udata = ffi::NewtonJointGetUserData(self.as_raw());
-            let udata: &Box<UserData> = mem::transmute(&udata);
-            udata.name
+            let udata = &*(ffi::NewtonJointGetUserData(self.as_raw()) as *const UserData);
+            udata.name.clone()
+        }
+    }
+
+    /// Names (or renames) the joint.
+    fn set_name<N: Into<Cow<'static, str>>>(&self, name: N) {
+        unsafe {
+            let udata = &mut ffi::NewtonJointGetUserData(self.as_raw());
+            let udata: &mut Box<UserData> = mem::transmute(udata);
+            udata.name = Some(name.into());
         }
     }
 
-    /// Returns the pair of bodies that are linked by this joint
-    fn bodies<'a: 'b, 'b>(&'a self) -> (Body<'b>, Option<Body<'b>>) {
+    /// Returns the pair of bodies that are linked by this joint.
+    ///
+    /// The second body is `None` for joints attached to the world. Bodies of types that
+    /// are not supported by this crate are also returned as `None`.
+    fn bodies<'a: 'b, 'b>(&'a self) -> (Option<Body<'b>>, Option<Body<'b>>) {
         unsafe {
             let body0 = ffi::NewtonJointGetBody0(self.as_raw());
             let body1 = ffi::NewtonJointGetBody1(self.as_raw());
 
-            let body0 = Body::from_raw(body0, false);
-            let body1 = if body1.is_null() { None } else { Some(Body::from_raw(body1, false)) };
+            let body0 = Body::try_from_raw(body0, false);
+            let body1 = if body1.is_null() { None } else { Body::try_from_raw(body1, false) };
 
             (body0, body1)
         }
@@ -8,14 +8,14 @@
 //!
 //! Newton supports running the simulation step on multiple threads, where bodies, collisions, and joints, are updated through application-implemented callbacks.
 //!
-//! `Collision`, `Body`, and `Joint` types are **NOT** `Sync` nor `Send`. You'll have to convert them to `Handle`s first.
+//! `Collision`, `Body`, and `Joint` types are **NOT** `Sync` nor `Send`. You'll have to convert them to handles first.
 //!
 //! [handle]: #
 pub use ffi;
 
 include!("macros.rs");
 
-/// Dynamic & Kinematic body wrappers.
+/// Dynamic, Kinematic & Deformable body wrappers.
 pub mod body;
 /// NewtonCollision wrappers.
 pub mod collision;
@@ -1,7 +1,7 @@
 macro_rules! bodies {
     ($(
         $(#[$($meta:meta)+])*
-        ($enum:ident, $ffi:ident, $option:ident) => pub struct $body:ident<'a>(...);
+        ($enum:ident, ffi::$enum_const:ident, $ffi:ident, $option:ident, $is:ident) => pub struct $body:ident<'a>(...);
     )*) => {
 
         /// Enum grouping all body types.
@@ -19,7 +19,9 @@ macro_rules! bodies {
         }
 
         impl<'a> $crate::handle::IntoHandle for $crate::body::Body<'a> {
-            fn into_handle(mut self, newton: &Newton) -> Handle {
+            type Handle = BodyHandle;
+
+            fn into_handle(mut self, newton: &Newton) -> BodyHandle {
                 match &mut self {
                     $(Body::$enum(ref mut body) => body.owned = false),*
                 }
@@ -28,57 +30,89 @@ macro_rules! bodies {
         }
 
         impl<'a> $crate::handle::AsHandle for $crate::body::Body<'a> {
-            fn as_handle(&self, _: &$crate::newton::Newton) -> Handle {
-                $crate::handle::Handle::from_ptr(self.as_raw() as _)
+            type Handle = BodyHandle;
+
+            fn as_handle(&self, _: &$crate::newton::Newton) -> BodyHandle {
+                $crate::handle::BodyHandle::from_ptr(self.as_raw() as _)
             }
         }
 
         impl<'a> $crate::handle::FromHandle<'a> for $crate::body::Body<'a> {
-            fn from_handle(newton: &'a $crate::newton::Newton, handle: $crate::handle::Handle) -> Option<Self> {
+            type Handle = BodyHandle;
+
+            fn from_handle(newton: &'a $crate::newton::Newton, handle: BodyHandle) -> Option<Self> {
                 newton.storage().body(handle)
             }
 
-            fn from_handle_owned(newton: &'a mut Newton, handle: Handle) -> Option<Self> {
+            fn from_handle_owned(newton: &'a mut Newton, handle: BodyHandle) -> Option<Self> {
                 newton.storage_mut().take_body(handle)
             }
         }
 
-        impl<'a> $crate::body::Body<'a> {
-            pub(crate) unsafe fn from_raw(raw: *const $crate::ffi::NewtonBody, owned: bool) -> Self {
-                let body_type = $crate::ffi::NewtonBodyGetType(raw);
-                match mem::transmute::<_, Type>(body_type) {
-                    Type::Dynamic => $crate::body::Body::Dynamic(DynamicBody::from_raw(raw, owned)),
-                    Type::Kinematic => $crate::body::Body::Kinematic(KinematicBody::from_raw(raw, owned)),
+        /// Body types.
+        #[repr(u32)]
+        #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+        pub enum Type {
+            $( $enum = ffi::$enum_const ),*
+        }
+
+        impl Type {
+            /// Maps the value returned by `NewtonBodyGetType` to a body type.
+            ///
+            /// Bodies handed back by Newton whose type maps to `None` are skipped
+            /// (in queries) or ignored (in callbacks) instead of panicking.
+            ///
+            /// ```
+            /// use newton::body::Type;
+            ///
+            /// assert_eq!(Some(Type::Dynamic), Type::from_raw(Type::Dynamic as i32));
+            /// assert_eq!(None, Type::from_raw(-1));
+            /// assert_eq!(None, Type::from_raw(0xff));
+            /// ```
+            pub fn from_raw(body_type: i32) -> Option<Self> {
+                match body_type as u32 {
+                    $( ffi::$enum_const => Some(Type::$enum), )*
+                    _ => None,
                 }
             }
+        }
 
-            pub fn dynamic(self) -> Option<DynamicBody<'a>> {
-                match self {
-                    Body::Dynamic(body) => Some(body),
-                    _ => None,
+        impl<'a> $crate::body::Body<'a> {
+            /// Wraps a raw body.
+            ///
+            /// ## Panics
+            ///
+            /// If the type of the body is not supported by this crate.
+            pub(crate) unsafe fn from_raw(raw: *const $crate::ffi::NewtonBody, owned: bool) -> Self {
+                match Self::try_from_raw(raw, owned) {
+                    Some(body) => body,
+                    None => panic!("Unsupported body type ({})", $crate::ffi::NewtonBodyGetType(raw)),
                 }
             }
 
-            pub fn kinematic(self) -> Option<KinematicBody<'a>> {
-                match self {
-                    Body::Kinematic(body) => Some(body),
-                    _ => None,
+            /// Wraps a raw body, or returns `None` if its type is not supported by this crate.
+            pub(crate) unsafe fn try_from_raw(raw: *const $crate::ffi::NewtonBody, owned: bool) -> Option<Self> {
+                match Type::from_raw($crate::ffi::NewtonBodyGetType(raw)) {
+                    $( Some(Type::$enum) => Some($crate::body::Body::$enum($body::from_raw(raw, owned))), )*
+                    None => None,
                 }
             }
 
-            pub fn is_dynamic(&self) -> bool {
-                match self {
-                    Body::Dynamic(_) => true,
-                    _ => false,
+            $(
+                pub fn $option(self) -> Option<$body<'a>> {
+                    match self {
+                        Body::$enum(body) => Some(body),
+                        _ => None,
+                    }
                 }
-            }
 
-            pub fn is_kinematic(&self) -> bool {
-                match self {
-                    Body::Kinematic(_) => true,
-                    _ => false,
+                pub fn $is(&self) -> bool {
+                    match self {
+                        Body::$enum(_) => true,
+                        _ => false,
+                    }
                 }
-            }
+            )*
         }
 
         $(
@@ -98,11 +132,13 @@ macro_rules! bodies {
             }
 
             impl<'a> FromHandle<'a> for $body<'a> {
-                fn from_handle(newton: &'a Newton, handle: Handle) -> Option<Self> {
+                type Handle = BodyHandle;
+
+                fn from_handle(newton: &'a Newton, handle: BodyHandle) -> Option<Self> {
                     newton.storage().body(handle).and_then(|h| h.$option())
                 }
 
-                fn from_handle_owned(newton: &'a mut Newton, handle: Handle) -> Option<Self> {
+                fn from_handle_owned(newton: &'a mut Newton, handle: BodyHandle) -> Option<Self> {
                     newton.storage_mut().take_body(handle).and_then(|h| h.$option())
                 }
             }
@@ -133,7 +169,9 @@ macro_rules! bodies {
             }
 
             impl<'a> IntoHandle for $body<'a> {
-                fn into_handle(mut self, newton: &Newton) -> Handle {
+                type Handle = BodyHandle;
+
+                fn into_handle(mut self, newton: &Newton) -> BodyHandle {
                     //if !self.owned { panic!() }
                     self.owned = false;
                     newton.storage().move_body(self.into_body())
@@ -141,8 +179,10 @@ macro_rules! bodies {
             }
 
             impl<'a> AsHandle for $body<'a> {
-                fn as_handle(&self, _: &Newton) -> Handle {
-                    Handle::from_ptr(self.raw as _)
+                type Handle = BodyHandle;
+
+                fn as_handle(&self, _: &Newton) -> BodyHandle {
+                    BodyHandle::from_ptr(self.raw as _)
                 }
             }
 
@@ -161,12 +201,23 @@ macro_rules! bodies {
                     self.owned = false;
                 }
 
-                pub fn create<C>(newton: &'a Newton,
-                                 collision: &C,
-                                 matrix: Mat4,
-                                 name: Option<&'static str>) -> Self
+                /// Returns a builder to configure the body before creating it.
+                pub fn builder<'c, C>(newton: &'a Newton, collision: &'c C) -> $crate::body::builder::BodyBuilder<'a, 'c, Self, C>
                 where
                     C: NewtonCollision,
+                {
+                    $crate::body::builder::BodyBuilder::new(newton, collision, |newton, collision, matrix| {
+                        Self::create(newton, collision, matrix, None::<&str>)
+                    })
+                }
+
+                pub fn create<C, N>(newton: &'a Newton,
+                                    collision: &C,
+                                    matrix: Mat4,
+                                    name: Option<N>) -> Self
+                where
+                    C: NewtonCollision,
+                    N: Into<Cow<'static, str>>,
                 {
                     unsafe {
                         let newton = newton.as_raw();
@@ -174,10 +225,7 @@ macro_rules! bodies {
                         let collision = collision.as_raw();
 
                         let body = $crate::ffi::$ffi(newton, collision, matrix);
-                        let userdata = Box::new(UserData { name, ..Default::default() });
-
-                        $crate::ffi::NewtonBodySetDestructorCallback(body, Some(body_destructor));
-                        $crate::ffi::NewtonBodySetUserData(body, mem::transmute(userdata));
+                        init_body(body, name.map(Into::into));
                         Self { raw: body, owned: true, _phantom: PhantomData }
                     }
                 }
@@ -216,25 +264,33 @@ macro_rules! collision {
     }
 
     /// Collision params
-    #[derive(Clone, Copy, Debug, PartialEq)]
+    ///
+    /// Params read from a height field don't include its horizontal displacement.
+    #[derive(Clone, Debug, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub enum Params<'a> {
         $( $param_name  $params ,)*
         HeightFieldF32(HeightFieldParams<'a, f32>),
         HeightFieldU16(HeightFieldParams<'a, u16>),
     }
 
-    #[derive(Clone, Copy, Debug, PartialEq)]
-    pub struct HeightFieldParams<'a, T: Elevation> {
+    #[derive(Clone, Debug, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct HeightFieldParams<'a, T: Elevation + Clone> {
         pub width: usize,
         pub height: usize,
-        pub vertical_elevation: &'a [T],
+        pub grid: HeightFieldGrid,
+        pub vertical_elevation: Cow<'a, [T]>,
         pub vertical_scale: f32,
         pub horizontal_scale_x: f32,
         pub horizontal_scale_z: f32,
-        pub horizontal_displacement_scale_x: f32,
-        pub horizontal_displacement_scale_z: f32,
-        pub horizontal_displacement: &'a [i16],
-        pub attributes: &'a [i8],
+        pub horizontal_displacement_scale: f32,
+        /// Empty if the vertices are not displaced.
+        ///
+        /// Newton doesn't report the displacement, so this is always empty (and the scale
+        /// zero) in the params read from a collision.
+        pub horizontal_displacement: Cow<'a, [u16]>,
+        pub attributes: Cow<'a, [i8]>,
     }
 
 /*
@@ -248,17 +304,21 @@ macro_rules! collision {
 */
 
     impl<'a> FromHandle<'a> for Collision<'a> {
-        fn from_handle(newton: &'a Newton, handle: Handle) -> Option<Self> {
+        type Handle = CollisionHandle;
+
+        fn from_handle(newton: &'a Newton, handle: CollisionHandle) -> Option<Self> {
             newton.storage().collision(handle)
         }
 
-        fn from_handle_owned(newton: &'a mut Newton, handle: Handle) -> Option<Self> {
+        fn from_handle_owned(newton: &'a mut Newton, handle: CollisionHandle) -> Option<Self> {
             newton.storage_mut().take_collision(handle)
         }
     }
 
     impl<'a> AsHandle for Collision<'a> {
-        fn as_handle(&self, newton: &Newton) -> Handle {
+        type Handle = CollisionHandle;
+
+        fn as_handle(&self, newton: &Newton) -> CollisionHandle {
             match self {
                 $(Collision::$enum_var(ref col) => col.as_handle(newton) ,)*
                 Collision::HeightFieldF32(ref col) => col.as_handle(newton),
@@ -274,7 +334,16 @@ macro_rules! collision {
                 $(
                     Type::$enum_var => Collision::$enum_var($collision::from_raw(raw, owned)),
                 )*
-                Type::HeightField => unimplemented!(),
+                Type::HeightField => {
+                    let mut info: $crate::ffi::NewtonCollisionInfoRecord = mem::zeroed();
+                    $crate::ffi::NewtonCollisionGetInfo(raw, &mut info);
+                    match info.__bindgen_anon_1.m_heightField.m_elevationDataType {
+                        t if t == <f32 as Elevation>::newton_enum() => {
+                            Collision::HeightFieldF32(HeightField::from_raw(raw, owned))
+                        }
+                        _ => Collision::HeightFieldU16(HeightField::from_raw(raw, owned)),
+                    }
+                }
                 //_ => unimplemented!("Collision type ({}) not implemented", col_type),
             }
         }
@@ -393,7 +462,9 @@ macro_rules! collision {
     }
 
     impl<'a> IntoHandle for HeightField<'a, u16> {
-        fn into_handle(mut self, newton: &Newton) -> Handle {
+        type Handle = CollisionHandle;
+
+        fn into_handle(mut self, newton: &Newton) -> CollisionHandle {
             //if !self.owned { panic!() }
             self.owned = false;
             newton.storage().move_collision(self.into_collision())
@@ -401,7 +472,9 @@ macro_rules! collision {
     }
 
     impl<'a> IntoHandle for HeightField<'a, f32> {
-        fn into_handle(mut self, newton: &Newton) -> Handle {
+        type Handle = CollisionHandle;
+
+        fn into_handle(mut self, newton: &Newton) -> CollisionHandle {
             //if !self.owned { panic!() }
             self.owned = false;
             newton.storage().move_collision(self.into_collision())
@@ -409,28 +482,34 @@ macro_rules! collision {
     }
 
     impl<'a> FromHandle<'a> for HeightField<'a, f32> {
-        fn from_handle(newton: &'a Newton, handle: Handle) -> Option<Self> {
+        type Handle = CollisionHandle;
+
+        fn from_handle(newton: &'a Newton, handle: CollisionHandle) -> Option<Self> {
             newton.storage().collision(handle).and_then(|h| h.height_field_f32())
         }
 
-        fn from_handle_owned(newton: &'a mut Newton, handle: Handle) -> Option<Self> {
+        fn from_handle_owned(newton: &'a mut Newton, handle: CollisionHandle) -> Option<Self> {
             newton.storage_mut().take_collision(handle).and_then(|h| h.height_field_f32())
         }
     }
 
     impl<'a> FromHandle<'a> for HeightField<'a, u16> {
-        fn from_handle(newton: &'a Newton, handle: Handle) -> Option<Self> {
+        type Handle = CollisionHandle;
+
+        fn from_handle(newton: &'a Newton, handle: CollisionHandle) -> Option<Self> {
             newton.storage().collision(handle).and_then(|h| h.height_field_u16())
         }
 
-        fn from_handle_owned(newton: &'a mut Newton, handle: Handle) -> Option<Self> {
+        fn from_handle_owned(newton: &'a mut Newton, handle: CollisionHandle) -> Option<Self> {
             newton.storage_mut().take_collision(handle).and_then(|h| h.height_field_u16())
         }
     }
 
     impl<'a, T: Elevation> AsHandle for HeightField<'a, T> {
-        fn as_handle(&self, _: &Newton) -> Handle {
-            Handle::from_ptr(self.raw as _)
+        type Handle = CollisionHandle;
+
+        fn as_handle(&self, _: &Newton) -> CollisionHandle {
+            CollisionHandle::from_ptr(self.raw as _)
         }
     }
 
@@ -472,11 +551,13 @@ macro_rules! collision {
         }
 
         impl<'a> FromHandle<'a> for $collision<'a> {
-            fn from_handle(newton: &'a Newton, handle: Handle) -> Option<Self> {
+            type Handle = CollisionHandle;
+
+            fn from_handle(newton: &'a Newton, handle: CollisionHandle) -> Option<Self> {
                 newton.storage().collision(handle).and_then(|h| h.$option())
             }
 
-            fn from_handle_owned(newton: &'a mut Newton, handle: Handle) -> Option<Self> {
+            fn from_handle_owned(newton: &'a mut Newton, handle: CollisionHandle) -> Option<Self> {
                 newton.storage_mut().take_collision(handle).and_then(|h| h.$option())
             }
         }
@@ -512,7 +593,9 @@ macro_rules! collision {
         }
 
         impl<'a> IntoHandle for $collision<'a> {
-            fn into_handle(mut self, newton: &Newton) -> Handle {
+            type Handle = CollisionHandle;
+
+            fn into_handle(mut self, newton: &Newton) -> CollisionHandle {
                 //if !self.owned { panic!() }
                 self.owned = false;
                 newton.storage().move_collision(self.into_collision())
@@ -520,8 +603,10 @@ macro_rules! collision {
         }
 
         impl<'a> AsHandle for $collision<'a> {
-            fn as_handle(&self, _: &Newton) -> Handle {
-                Handle::from_ptr(self.raw as _)
+            type Handle = CollisionHandle;
+
+            fn as_handle(&self, _: &Newton) -> CollisionHandle {
+                CollisionHandle::from_ptr(self.raw as _)
             }
         }
 
@@ -591,15 +676,36 @@ macro_rules! joints {
             }
 
             impl<'a> AsHandle for $joint<'a> {
-                fn as_handle(&self, _: &Newton) -> Handle {
-                    Handle::from_ptr(self.raw as _)
+                type Handle = JointHandle;
+
+                fn as_handle(&self, _: &Newton) -> JointHandle {
+                    JointHandle::from_ptr(self.raw as _)
                 }
             }
             impl<'a> IntoHandle for $joint<'a> {
-                fn into_handle(mut self, newton: &Newton) -> Handle {
+                type Handle = JointHandle;
+
+                fn into_handle(mut self, newton: &Newton) -> JointHandle {
                     self.owned = false;
-                    //newton.storage().move_constraint(newton);
-                    Handle::from_ptr(self.raw as _)
+                    newton.storage().move_constraint(Joint::$joint(self))
+                }
+            }
+
+            impl<'a> FromHandle<'a> for $joint<'a> {
+                type Handle = JointHandle;
+
+                fn from_handle(newton: &'a Newton, handle: JointHandle) -> Option<Self> {
+                    newton.storage().constraint(handle).and_then(|h| h.$option())
+                }
+
+                fn from_handle_owned(newton: &'a mut Newton, handle: JointHandle) -> Option<Self> {
+                    newton.storage_mut().take_constraint(handle).and_then(|h| h.$option())
+                }
+            }
+
+            impl<'a> From<$joint<'a>> for Joint<'a> {
+                fn from(joint: $joint<'a>) -> Self {
+                    Joint::$joint(joint)
                 }
             }
         )*
@@ -651,6 +757,37 @@ macro_rules! joints {
                 }
             }
         }
+
+        impl<'a> IntoHandle for Joint<'a> {
+            type Handle = JointHandle;
+
+            fn into_handle(mut self, newton: &Newton) -> JointHandle {
+                match &mut self {
+                    $(Joint::$joint(ref mut joint) => joint.owned = false),*
+                }
+                newton.storage().move_constraint(self)
+            }
+        }
+
+        impl<'a> AsHandle for Joint<'a> {
+            type Handle = JointHandle;
+
+            fn as_handle(&self, _: &Newton) -> JointHandle {
+                JointHandle::from_ptr(self.as_raw() as _)
+            }
+        }
+
+        impl<'a> FromHandle<'a> for Joint<'a> {
+            type Handle = JointHandle;
+
+            fn from_handle(newton: &'a Newton, handle: JointHandle) -> Option<Self> {
+                newton.storage().constraint(handle)
+            }
+
+            fn from_handle_owned(newton: &'a mut Newton, handle: JointHandle) -> Option<Self> {
+                newton.storage_mut().take_constraint(handle)
+            }
+        }
     }
 }
 
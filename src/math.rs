@@ -29,3 +29,118 @@ pub fn get_euler_angles(matrix: Mat4) -> (Vec3, Vec3) {
     }
     (sel0, sel1)
 }
+
+// Vector helpers used internally.
+
+pub(crate) fn sub([ax, ay, az]: Vec3, [bx, by, bz]: Vec3) -> Vec3 {
+    [ax - bx, ay - by, az - bz]
+}
+
+pub(crate) fn mul([x, y, z]: Vec3, s: f32) -> Vec3 {
+    [x * s, y * s, z * s]
+}
+
+pub(crate) fn cross([ax, ay, az]: Vec3, [bx, by, bz]: Vec3) -> Vec3 {
+    [ay * bz - az * by, az * bx - ax * bz, ax * by - ay * bx]
+}
+
+pub(crate) fn length([x, y, z]: Vec3) -> f32 {
+    (x * x + y * y + z * z).sqrt()
+}
+
+pub(crate) fn normalize(v: Vec3) -> Vec3 {
+    let len = length(v);
+    if len > 1e-6 {
+        mul(v, 1.0 / len)
+    } else {
+        [0.0, 0.0, 0.0]
+    }
+}
+
+/// Rotation matrix of a unit quaternion, laid out as `[w, x, y, z]` like Newton does.
+pub(crate) fn quat_to_mat([w, x, y, z]: Quat) -> Mat4 {
+    [[1.0 - 2.0 * (y * y + z * z), 2.0 * (x * y + w * z), 2.0 * (x * z - w * y), 0.0],
+     [2.0 * (x * y - w * z), 1.0 - 2.0 * (x * x + z * z), 2.0 * (y * z + w * x), 0.0],
+     [2.0 * (x * z + w * y), 2.0 * (y * z - w * x), 1.0 - 2.0 * (x * x + y * y), 0.0],
+     [0.0, 0.0, 0.0, 1.0]]
+}
+
+pub(crate) fn add([ax, ay, az]: Vec3, [bx, by, bz]: Vec3) -> Vec3 {
+    [ax + bx, ay + by, az + bz]
+}
+
+pub(crate) fn lerp(a: Vec3, b: Vec3, t: f32) -> Vec3 {
+    add(a, mul(sub(b, a), t))
+}
+
+pub(crate) fn quat_mul([aw, ax, ay, az]: Quat, [bw, bx, by, bz]: Quat) -> Quat {
+    [aw * bw - ax * bx - ay * by - az * bz,
+     aw * bx + ax * bw + ay * bz - az * by,
+     aw * by - ax * bz + ay * bw + az * bx,
+     aw * bz + ax * by - ay * bx + az * bw]
+}
+
+pub(crate) fn quat_conjugate([w, x, y, z]: Quat) -> Quat {
+    [w, -x, -y, -z]
+}
+
+/// Spherical interpolation, along the shortest arc.
+pub(crate) fn quat_slerp(a: Quat, mut b: Quat, t: f32) -> Quat {
+    let mut dot = (0..4).map(|i| a[i] * b[i]).sum::<f32>();
+    if dot < 0.0 {
+        dot = -dot;
+        b = [-b[0], -b[1], -b[2], -b[3]];
+    }
+    let (wa, wb) = if dot > 0.9995 {
+        (1.0 - t, t)
+    } else {
+        let theta = dot.acos();
+        let sin = theta.sin();
+        (((1.0 - t) * theta).sin() / sin, (t * theta).sin() / sin)
+    };
+    let q = [wa * a[0] + wb * b[0], wa * a[1] + wb * b[1], wa * a[2] + wb * b[2], wa * a[3] + wb * b[3]];
+    let len = (0..4).map(|i| q[i] * q[i]).sum::<f32>().sqrt();
+    [q[0] / len, q[1] / len, q[2] / len, q[3] / len]
+}
+
+/// Rotation vector (axis scaled by the angle, in radians) of a unit quaternion.
+pub(crate) fn quat_to_rotation_vector(q: Quat) -> Vec3 {
+    let [w, x, y, z] = if q[0] < 0.0 { [-q[0], -q[1], -q[2], -q[3]] } else { q };
+    let sin = length([x, y, z]);
+    if sin < 1e-6 {
+        return mul([x, y, z], 2.0);
+    }
+    let angle = 2.0 * sin.atan2(w);
+    mul([x, y, z], angle / sin)
+}
+
+pub(crate) fn dot([ax, ay, az]: Vec3, [bx, by, bz]: Vec3) -> f32 {
+    ax * bx + ay * by + az * bz
+}
+
+fn column(matrix: &Mat4, i: usize) -> Vec3 {
+    let [x, y, z, _] = matrix[i];
+    [x, y, z]
+}
+
+/// Rotates a direction by a rigid transformation.
+pub(crate) fn rotate(matrix: &Mat4, [x, y, z]: Vec3) -> Vec3 {
+    add(add(mul(column(matrix, 0), x), mul(column(matrix, 1), y)), mul(column(matrix, 2), z))
+}
+
+/// Transforms a point to the local space of a rigid transformation.
+pub(crate) fn untransform(matrix: &Mat4, point: Vec3) -> Vec3 {
+    let p = sub(point, column(matrix, 3));
+    [dot(column(matrix, 0), p), dot(column(matrix, 1), p), dot(column(matrix, 2), p)]
+}
+
+/// Product of two matrices (`a` applied after `b`).
+pub(crate) fn mat_mul(a: &Mat4, b: &Mat4) -> Mat4 {
+    let mut r: Mat4 = Default::default();
+    for c in 0..4 {
+        for row in 0..4 {
+            r[c][row] = (0..4).map(|k| a[k][row] * b[c][k]).sum();
+        }
+    }
+    r
+}
@@ -0,0 +1,124 @@
+use std::mem;
+use std::sync::Mutex;
+
+use crate::body::NewtonBody;
+use crate::ffi;
+use crate::handle::{BodyHandle, JointHandle};
+use crate::math::Mat4;
+
+use super::{Newton, UserData};
+
+/// A world mutation deferred until the end of the current update.
+pub(crate) enum Command {
+    Spawn(Box<dyn FnOnce(&Newton) + Send>),
+    Despawn(BodyHandle),
+    SetMatrix(BodyHandle, Mat4),
+    AddJoint(Box<dyn FnOnce(&Newton) + Send>),
+    DestroyJoint(JointHandle),
+}
+
+/// Buffer of world mutations.
+///
+/// Creating or destroying bodies & joints is not allowed while the world is being updated,
+/// which rules out doing it from the force & torque, transform, or contact callbacks.
+/// Operations queued in this buffer are applied, in order, right after `Newton::update`
+/// returns (or when an asynchronous update finishes).
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use newton::prelude::*;
+/// use newton::{DynamicBody, Newton, Sphere};
+///
+/// let mut newton = Newton::create();
+///
+/// let handle = {
+///     let sphere = Sphere::create(&newton, 1.0, None);
+///     let body = DynamicBody::create(&newton, &sphere, identity(), Some("doomed"));
+///     body.set_mass(1.0, &sphere);
+///
+///     // destroy the body once the current update is over
+///     let handle = body.as_handle(&newton);
+///     body.set_force_and_torque_callback(move |b, _, _| b.commands().despawn(handle));
+///     body.into_handle(&newton)
+/// };
+///
+/// assert!(newton.storage().body(handle).is_some());
+/// newton.update(Duration::new(0, 1_000_000_000 / 60));
+/// assert!(newton.storage().body(handle).is_none());
+///
+/// # fn identity() -> [[f32; 4]; 4] {
+/// #     [[1.0, 0.0, 0.0, 0.0], [0.0, 1.0, 0.0, 0.0], [0.0, 0.0, 1.0, 0.0], [0.0, 0.0, 0.0, 1.0]]
+/// # }
+/// ```
+pub struct Commands<'a> {
+    queue: &'a Mutex<Vec<Command>>,
+}
+
+impl<'a> Commands<'a> {
+    pub(crate) unsafe fn from_world(world: *const ffi::NewtonWorld) -> Self {
+        Self { queue: &UserData::from_world(world).commands }
+    }
+
+    fn push(&self, command: Command) {
+        self.queue.lock().unwrap().push(command);
+    }
+
+    /// Runs the given closure after the update, where it can create new bodies,
+    /// collisions, and move them to the world storage.
+    pub fn spawn<F>(&self, spawn: F)
+        where F: FnOnce(&Newton) + Send + 'static
+    {
+        self.push(Command::Spawn(Box::new(spawn)));
+    }
+
+    /// Destroys a body stored in the world storage.
+    ///
+    /// Handles of bodies that are no longer (or were never) in the storage are ignored.
+    pub fn despawn(&self, body: BodyHandle) {
+        self.push(Command::Despawn(body));
+    }
+
+    /// Sets the matrix of a body stored in the world storage.
+    pub fn set_matrix(&self, body: BodyHandle, matrix: Mat4) {
+        self.push(Command::SetMatrix(body, matrix));
+    }
+
+    /// Runs the given closure after the update, where it can create new joints.
+    pub fn add_joint<F>(&self, add: F)
+        where F: FnOnce(&Newton) + Send + 'static
+    {
+        self.push(Command::AddJoint(Box::new(add)));
+    }
+
+    /// Destroys a joint stored in the world storage.
+    pub fn destroy_joint(&self, joint: JointHandle) {
+        self.push(Command::DestroyJoint(joint));
+    }
+}
+
+/// Applies all the queued commands.
+pub(crate) fn apply(newton: &mut Newton) {
+    let commands = {
+        let udata = unsafe { UserData::from_world(newton.as_raw()) };
+        mem::replace(&mut *udata.commands.lock().unwrap(), Vec::new())
+    };
+
+    for command in commands {
+        match command {
+            Command::Spawn(spawn) => spawn(newton),
+            Command::Despawn(body) => {
+                let _ = newton.storage_mut().take_body(body);
+            }
+            Command::SetMatrix(body, matrix) => {
+                if let Some(body) = newton.storage().body(body) {
+                    body.set_matrix(matrix);
+                }
+            }
+            Command::AddJoint(add) => add(newton),
+            Command::DestroyJoint(joint) => {
+                let _ = newton.storage_mut().take_constraint(joint);
+            }
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use std::f32;
+
+use crate::body::NewtonBody;
+use crate::ffi;
+use crate::math::{cross, length, mul, normalize, sub, Vec3};
+
+use super::UserData;
+
+/// Strength of a force field as a function of the distance to its centre.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Falloff {
+    /// Same strength everywhere within the field region.
+    Constant,
+    /// Strength decreases linearly, reaching zero at the given radius.
+    Linear { radius: f32 },
+    /// Strength decreases with the square of the distance (beyond a distance of 1).
+    InverseSquare,
+}
+
+/// Kinds of force field.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FieldKind {
+    /// Constant acceleration, independent of the mass of the bodies.
+    Directional { acceleration: Vec3 },
+    /// Accelerates bodies towards a point (or away from it, if the strength is negative).
+    Attractor { centre: Vec3, strength: f32 },
+    /// Accelerates bodies around an axis that goes through `centre`.
+    Vortex { centre: Vec3, axis: Vec3, strength: f32 },
+    /// Drags bodies towards the wind velocity.
+    Wind { velocity: Vec3, drag: f32 },
+}
+
+/// A force applied to all dynamic bodies whose AABB overlaps the region of the field.
+///
+/// Fields are registered with `Newton::add_force_field`, and evaluated after the force &
+/// torque callback of each body.
+///
+/// ```
+/// use newton::Newton;
+/// use newton::newton::force_field::{Falloff, ForceField};
+///
+/// let newton = Newton::config().gravity([0.0, -9.8, 0.0]).build();
+///
+/// // updraft
+/// let field = ForceField::directional([0.0, 20.0, 0.0])
+///     .region([-2.0, 0.0, -2.0], [2.0, 8.0, 2.0])
+///     .falloff(Falloff::Linear { radius: 4.0 });
+///
+/// let handle = newton.add_force_field(field);
+/// # newton.remove_force_field(handle);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ForceField {
+    pub kind: FieldKind,
+    /// Minimum corner of the field region.
+    pub min: Vec3,
+    /// Maximum corner of the field region.
+    pub max: Vec3,
+    pub falloff: Falloff,
+}
+
+impl ForceField {
+    /// Creates an unbounded field with no falloff.
+    pub fn new(kind: FieldKind) -> Self {
+        Self { kind,
+               min: [f32::NEG_INFINITY; 3],
+               max: [f32::INFINITY; 3],
+               falloff: Falloff::Constant }
+    }
+
+    pub fn directional(acceleration: Vec3) -> Self {
+        Self::new(FieldKind::Directional { acceleration })
+    }
+
+    pub fn attractor(centre: Vec3, strength: f32) -> Self {
+        Self::new(FieldKind::Attractor { centre, strength })
+    }
+
+    pub fn vortex(centre: Vec3, axis: Vec3, strength: f32) -> Self {
+        Self::new(FieldKind::Vortex { centre, axis, strength })
+    }
+
+    pub fn wind(velocity: Vec3, drag: f32) -> Self {
+        Self::new(FieldKind::Wind { velocity, drag })
+    }
+
+    /// Limits the field to the given axis-aligned box.
+    pub fn region(mut self, min: Vec3, max: Vec3) -> Self {
+        self.min = min;
+        self.max = max;
+        self
+    }
+
+    pub fn falloff(mut self, falloff: Falloff) -> Self {
+        self.falloff = falloff;
+        self
+    }
+
+    /// Point the falloff is measured from. Either the centre of the field, or the centre
+    /// of its region for directional fields.
+    fn centre(&self) -> Vec3 {
+        match self.kind {
+            FieldKind::Attractor { centre, .. } | FieldKind::Vortex { centre, .. } => centre,
+            _ => {
+                let [x0, y0, z0] = self.min;
+                let [x1, y1, z1] = self.max;
+                let mid = |a: f32, b: f32| {
+                    if a.is_finite() && b.is_finite() {
+                        (a + b) * 0.5
+                    } else {
+                        0.0
+                    }
+                };
+                [mid(x0, x1), mid(y0, y1), mid(z0, z1)]
+            }
+        }
+    }
+
+    fn overlaps(&self, (min, max): (Vec3, Vec3)) -> bool {
+        (0..3).all(|i| min[i] <= self.max[i] && max[i] >= self.min[i])
+    }
+
+    /// Computes the force applied to a body.
+    fn force<B: NewtonBody>(&self, body: &B) -> Vec3 {
+        let position = body.position();
+        let (mass, _) = body.mass();
+
+        let scale = match self.falloff {
+            Falloff::Constant => 1.0,
+            Falloff::Linear { radius } => {
+                let dist = length(sub(position, self.centre()));
+                (1.0 - dist / radius).max(0.0)
+            }
+            Falloff::InverseSquare => {
+                let dist = length(sub(position, self.centre()));
+                1.0 / (dist * dist).max(1.0)
+            }
+        };
+
+        match self.kind {
+            FieldKind::Directional { acceleration } => mul(acceleration, mass * scale),
+            FieldKind::Attractor { centre, strength } => {
+                mul(normalize(sub(centre, position)), mass * strength * scale)
+            }
+            FieldKind::Vortex { centre, axis, strength } => {
+                let axis = normalize(axis);
+                let radial = sub(position, centre);
+                let tangent = normalize(cross(axis, radial));
+                mul(tangent, mass * strength * scale)
+            }
+            FieldKind::Wind { velocity, drag } => mul(sub(velocity, body.velocity()), drag * scale),
+        }
+    }
+}
+
+/// Applies world gravity (only if `gravity` is set) and all the force fields to a body.
+/// Called from the force & torque callback.
+pub(crate) fn apply<B: NewtonBody>(body: &B, gravity: bool) {
+    let udata = unsafe { UserData::from_world(ffi::NewtonBodyGetWorld(body.as_raw())) };
+
+    if let (true, Some(g)) = (gravity, udata.gravity) {
+        let (mass, _) = body.mass();
+        body.add_force(mul(g, mass));
+    }
+
+    let fields = udata.force_fields.read().unwrap();
+    if fields.is_empty() {
+        return;
+    }
+
+    let aabb = body.aabb();
+    for (_, field) in fields.iter() {
+        if field.overlaps(aabb) {
+            body.add_force(field.force(body));
+        }
+    }
+}
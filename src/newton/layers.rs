@@ -0,0 +1,57 @@
+use std::os::raw::c_int;
+
+use crate::body::{Body, NewtonBody};
+use crate::ffi;
+
+use super::UserData;
+
+/// Number of collision layers.
+pub const LAYERS: usize = 32;
+
+/// Collision matrix with every pair of layers enabled.
+pub(crate) fn default_matrix() -> [u32; LAYERS] {
+    [!0; LAYERS]
+}
+
+/// Installs the layer filter on the default material pair.
+pub(crate) unsafe fn install(world: *const ffi::NewtonWorld) {
+    add_group(world, ffi::NewtonMaterialGetDefaultGroupID(world));
+}
+
+/// Installs the layer filter on the pairs a new material group forms with every group
+/// known to the world, itself included.
+pub(crate) unsafe fn add_group(world: *const ffi::NewtonWorld, id: c_int) {
+    let mut groups = UserData::from_world(world).material_groups.lock().unwrap();
+    groups.push(id);
+    for &other in groups.iter() {
+        ffi::NewtonMaterialSetCollisionCallback(world, id, other, Some(aabb_overlap), None);
+    }
+}
+
+/// Whether a pair of bodies passes both the world layer matrix and their masks.
+pub(crate) fn can_collide<A: NewtonBody, B: NewtonBody>(matrix: &[u32; LAYERS], a: &A, b: &B) -> bool {
+    let (la, lb) = (a.layer() as u32, b.layer() as u32);
+    matrix[la as usize] & (1 << lb) != 0
+    && a.collision_mask() & (1 << lb) != 0
+    && b.collision_mask() & (1 << la) != 0
+}
+
+// Called by Newton when the AABBs of two bodies start overlapping, before any contact
+// is computed. Returning 0 discards the pair.
+unsafe extern "C" fn aabb_overlap(contact: *const ffi::NewtonJoint, _: f32, _: c_int) -> c_int {
+    let a = ffi::NewtonJointGetBody0(contact);
+    let b = ffi::NewtonJointGetBody1(contact);
+    let world = ffi::NewtonBodyGetWorld(a);
+
+    let (a, b) = match (Body::try_from_raw(a, false), Body::try_from_raw(b, false)) {
+        (Some(a), Some(b)) => (a, b),
+        // bodies of unsupported types are never filtered
+        _ => return 1,
+    };
+    let matrix = UserData::from_world(world).layer_matrix.read().unwrap();
+    if can_collide(&matrix, &a, &b) {
+        1
+    } else {
+        0
+    }
+}
@@ -1,16 +1,34 @@
+use std::any::Any;
+use std::collections::HashSet;
 use std::marker::PhantomData;
 use std::mem;
-use std::os::raw::{c_longlong, c_void};
+use std::os::raw::{c_int, c_longlong, c_void};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, RwLock};
 use std::time::Duration;
 
+use slab::Slab;
+
+use commands::{Command, Commands};
+use force_field::ForceField;
+use layers::LAYERS;
 use ray_cast::RayCastAlgorithm;
 use storage::{BTreeStorage, NewtonStorage};
 
-use crate::body::{iter::Bodies, Body, NewtonBody};
+use crate::body::{self, iter::Bodies, Body, NewtonBody, SleepState};
 use crate::collision::{Collision, ConvexShape, NewtonCollision};
 use crate::ffi;
+use crate::handle::ForceFieldHandle;
 use crate::math::{Mat4, Vec3, Vec4};
 
+/// Deferred world mutations.
+pub mod commands;
+/// World-level force fields.
+pub mod force_field;
+/// Collision layers.
+pub mod layers;
 /// Implementation of useful ray_cast algorithms.
 pub mod ray_cast;
 /// Data structured for bodies & collisions.
@@ -18,7 +36,7 @@ pub mod storage;
 
 /// Type returned by an asynchronous update.
 #[derive(Debug)]
-pub struct AsyncUpdate<'a>(&'a Newton);
+pub struct AsyncUpdate<'a>(&'a mut Newton);
 
 impl<'a> AsyncUpdate<'a> {
     /// Waits for the newton world update to finish, blocking the current thread.
@@ -29,6 +47,31 @@ impl<'a> Drop for AsyncUpdate<'a> {
     fn drop(&mut self) {
         let world = self.0.as_raw();
         unsafe { ffi::NewtonWaitForUpdateToFinish(world) }
+        self.0.after_update();
+    }
+}
+
+/// Guard of the world critical section, returned by `Newton::critical_section` and
+/// `NewtonBody::critical_section`.
+///
+/// Use it to mutate shared application state from callbacks running on multiple threads
+/// (force & torque, transform, etc). The critical section is left when the guard is dropped.
+#[derive(Debug)]
+pub struct CriticalSection<'a> {
+    world: *const ffi::NewtonWorld,
+    _phantom: PhantomData<&'a ()>,
+}
+
+impl<'a> CriticalSection<'a> {
+    pub(crate) unsafe fn lock(world: *const ffi::NewtonWorld, thread_idx: usize) -> Self {
+        ffi::NewtonWorldCriticalSectionLock(world, thread_idx as _);
+        Self { world, _phantom: PhantomData }
+    }
+}
+
+impl<'a> Drop for CriticalSection<'a> {
+    fn drop(&mut self) {
+        unsafe { ffi::NewtonWorldCriticalSectionUnlock(self.world) }
     }
 }
 
@@ -56,17 +99,87 @@ impl<'a> ConvexCastResult<'a> {
         self.info.len()
     }
 
+    /// Returns `None` if the index is out of bounds, or the body that was hit is of a type
+    /// not supported by this crate.
     pub fn get(&self, index: usize) -> Option<ConvexCastInfo> {
-        self.info.get(index).map(|info| ConvexCastInfo { body: unsafe {
-                                                             Body::from_raw(info.m_hitBody, false)
-                                                         },
-                                                         point: info.m_point,
-                                                         normal: info.m_normal,
-                                                         contact_id: info.m_contactID,
-                                                         penetration: info.m_penetration })
+        let info = self.info.get(index)?;
+        let body = unsafe { Body::try_from_raw(info.m_hitBody, false)? };
+        Some(ConvexCastInfo { body,
+                              point: info.m_point,
+                              normal: info.m_normal,
+                              contact_id: info.m_contactID,
+                              penetration: info.m_penetration })
     }
 }
 
+/// Group of bodies that touch each other or are connected by joints, passed to the
+/// callback set with `Newton::set_island_callback`.
+#[derive(Debug)]
+pub struct Island<'a> {
+    raw: *const c_void,
+    len: usize,
+    _phantom: PhantomData<&'a ()>,
+}
+
+impl<'a> Island<'a> {
+    /// Number of bodies in the island.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn body(&self, index: usize) -> Option<Body> {
+        if index >= self.len {
+            return None;
+        }
+        unsafe { Body::try_from_raw(ffi::NewtonIslandGetBody(self.raw, index as _), false) }
+    }
+
+    pub fn bodies(&self) -> impl Iterator<Item = Body> + '_ {
+        (0..self.len).filter_map(move |i| self.body(i))
+    }
+
+    // Whether every body of the island auto-sleeps, and moves slower than the given speeds.
+    unsafe fn is_resting(&self, linear: f32, angular: f32) -> bool {
+        let len2 = |[x, y, z]: Vec3| x * x + y * y + z * z;
+        (0..self.len).all(|i| {
+                         let body = ffi::NewtonIslandGetBody(self.raw, i as _);
+                         let mut velocity = [0.0, 0.0, 0.0];
+                         let mut omega = [0.0, 0.0, 0.0];
+                         ffi::NewtonBodyGetVelocity(body, velocity.as_mut_ptr());
+                         ffi::NewtonBodyGetOmega(body, omega.as_mut_ptr());
+                         ffi::NewtonBodyGetAutoSleep(body) == 1
+                         && len2(velocity) < linear * linear
+                         && len2(omega) < angular * angular
+                     })
+    }
+
+    /// AABB of the body at the given index.
+    pub fn body_aabb(&self, index: usize) -> Option<(Vec3, Vec3)> {
+        if index >= self.len {
+            return None;
+        }
+        let mut min = [0.0, 0.0, 0.0];
+        let mut max = [0.0, 0.0, 0.0];
+        unsafe {
+            ffi::NewtonIslandGetBodyAABB(self.raw, index as _, min.as_mut_ptr(), max.as_mut_ptr())
+        }
+        Some((min, max))
+    }
+}
+
+// Data shared by the jobs dispatched from `par_for_each_body`.
+struct ParallelJob<'a, F> {
+    bodies: &'a [*const ffi::NewtonBody],
+    next: AtomicUsize,
+    callback: &'a F,
+    /// Payload of the first panic raised by the callback.
+    panic: Mutex<Option<Box<dyn Any + Send>>>,
+}
+
 /// Wrapper around NewtonWorld.
 #[derive(Debug)]
 pub struct Newton {
@@ -84,6 +197,39 @@ unsafe impl Sync for Newton {}
 // An alternative to remove this indirection is to make Newton generic (Newton<S>) over the NewtonStorage.
 struct UserData {
     storage: Box<dyn NewtonStorage>,
+
+    /// World mutations queued from callbacks.
+    commands: Mutex<Vec<Command>>,
+
+    /// Linear & angular speed thresholds below which islands are put to sleep.
+    sleep_threshold: Option<(f32, f32)>,
+
+    /// Bodies of the islands that fell below the sleep threshold during the last update.
+    resting: Mutex<Vec<*const ffi::NewtonBody>>,
+
+    /// Default gravity, for bodies without a force & torque callback.
+    gravity: Option<Vec3>,
+
+    force_fields: RwLock<Slab<ForceField>>,
+
+    /// Bodies with a sleep callback.
+    sleep_watched: Mutex<HashSet<*const ffi::NewtonBody>>,
+
+    /// Collision matrix. Bit `j` of row `i` is set if layers `i` and `j` collide.
+    layer_matrix: RwLock<[u32; LAYERS]>,
+
+    /// Material groups whose pairs have the layer filter installed.
+    material_groups: Mutex<Vec<c_int>>,
+
+    /// Island update callback.
+    island: RwLock<Option<Box<dyn Fn(&Island) -> bool + Send + Sync>>>,
+}
+
+impl UserData {
+    unsafe fn from_world<'a>(world: *const ffi::NewtonWorld) -> &'a UserData {
+        let udata = ffi::NewtonWorldGetUserData(world);
+        &*(udata as *const UserData)
+    }
 }
 
 /// Newton World builder.
@@ -91,6 +237,8 @@ struct UserData {
 pub struct NewtonConfig {
     threads: Option<usize>,
     linear_steps: Option<usize>,
+    sleep_threshold: Option<(f32, f32)>,
+    gravity: Option<Vec3>,
     storage: Option<Box<dyn NewtonStorage>>,
 }
 
@@ -111,6 +259,54 @@ impl NewtonConfig {
         self
     }
 
+    /// Puts to sleep the islands (groups of touching or jointed bodies) whose bodies all
+    /// auto-sleep, and whose linear and angular speeds all fall below the given thresholds.
+    ///
+    /// This complements Newton's own (fixed) sleep heuristics, for bodies that take
+    /// too long to settle. Islands are checked before they are simulated, and put to sleep
+    /// right after the update.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use newton::prelude::*;
+    /// use newton::body::SleepState;
+    /// use newton::{DynamicBody, Newton, Sphere};
+    ///
+    /// let mut newton = Newton::config().sleep_threshold(0.5, 0.5).build();
+    /// let (slow, fast, pushed) = {
+    ///     let sphere = Sphere::create(&newton, 0.5, None);
+    ///     let body = |position, velocity| {
+    ///         DynamicBody::builder(&newton, &sphere).position(position)
+    ///                                               .velocity(velocity)
+    ///                                               .mass(1.0)
+    ///                                               .into_handle()
+    ///     };
+    ///     let slow = body([0.0, 0.0, 0.0], [0.1, 0.0, 0.0]);
+    ///     let fast = body([10.0, 0.0, 0.0], [5.0, 0.0, 0.0]);
+    ///     // touches the fast body, so both are in the same island
+    ///     let pushed = body([10.9, 0.0, 0.0], [0.1, 0.0, 0.0]);
+    ///     (slow, fast, pushed)
+    /// };
+    ///
+    /// newton.update(Duration::new(0, 1_000_000_000 / 60));
+    ///
+    /// let state = |handle| newton.storage().body(handle).unwrap().sleep_state();
+    /// assert_eq!(SleepState::Sleeping, state(slow));
+    /// assert_eq!(SleepState::Active, state(fast));
+    /// assert_eq!(SleepState::Active, state(pushed));
+    /// ```
+    pub fn sleep_threshold(mut self, linear: f32, angular: f32) -> Self {
+        self.sleep_threshold = Some((linear, angular));
+        self
+    }
+
+    /// Gravity acceleration, applied to every body that has no force & torque callback.
+    pub fn gravity(mut self, gravity: Vec3) -> Self {
+        self.gravity = Some(gravity);
+        self
+    }
+
     pub fn storage<S: NewtonStorage + 'static>(mut self, storage: S) -> Self {
         self.storage = Some(Box::new(storage));
         self
@@ -139,9 +335,23 @@ impl Newton {
             let raw = ffi::NewtonCreate();
 
             let storage = conf.storage.unwrap_or(Box::new(BTreeStorage::default()));
-            let storage = Box::new(UserData { storage });
+            let storage = Box::new(UserData { storage,
+                                              commands: Mutex::new(Vec::new()),
+                                              sleep_threshold: conf.sleep_threshold,
+                                              resting: Mutex::new(Vec::new()),
+                                              gravity: conf.gravity,
+                                              force_fields: RwLock::new(Slab::new()),
+                                              sleep_watched: Mutex::new(HashSet::new()),
+                                              island: RwLock::new(None),
+                                              layer_matrix: RwLock::new(layers::default_matrix()),
+                                              material_groups: Mutex::new(Vec::new()) });
 
             ffi::NewtonWorldSetUserData(raw, mem::transmute(storage));
+            layers::install(raw);
+
+            if conf.sleep_threshold.is_some() {
+                ffi::NewtonSetIslandUpdateEvent(raw, Some(island_update));
+            }
 
             if let Some(threads) = conf.threads {
                 ffi::NewtonSetThreadsCount(raw, threads as _);
@@ -177,6 +387,51 @@ impl Newton {
         }
     }
 
+    pub(crate) unsafe fn storage_from_world<'a>(world: *const ffi::NewtonWorld)
+                                                -> &'a Box<dyn NewtonStorage> {
+        &UserData::from_world(world).storage
+    }
+
+    /// Borrows a body from the storage by name.
+    ///
+    /// If more than one body shares the same name, any of them may be returned.
+    ///
+    /// ```
+    /// use newton::prelude::*;
+    /// use newton::{DynamicBody, Newton, Sphere};
+    ///
+    /// let mut newton = Newton::create();
+    /// let (crate_a, barrel) = {
+    ///     let sphere = Sphere::create(&newton, 0.5, None);
+    ///     let crate_a = DynamicBody::builder(&newton, &sphere).name("crate_a").into_handle();
+    ///     DynamicBody::builder(&newton, &sphere).name("crate_b").into_handle();
+    ///     let barrel = DynamicBody::builder(&newton, &sphere).name(String::from("barrel")).into_handle();
+    ///     (crate_a, barrel)
+    /// };
+    ///
+    /// assert_eq!(Some("barrel"), newton.find_body("barrel").and_then(|b| b.name()).as_deref());
+    /// assert_eq!(2, newton.find_bodies("crate_").len());
+    /// assert!(newton.find_body("crate").is_none());
+    ///
+    /// // renaming a stored body updates the index
+    /// newton.storage().body(barrel).unwrap().set_name("crate_c");
+    /// assert!(newton.find_body("barrel").is_none());
+    /// assert_eq!(3, newton.find_bodies("crate_").len());
+    ///
+    /// // and so does taking it out of the storage
+    /// drop(newton.storage_mut().take_body(crate_a));
+    /// assert!(newton.find_body("crate_a").is_none());
+    /// assert_eq!(2, newton.find_bodies("crate_").len());
+    /// ```
+    pub fn find_body(&self, name: &str) -> Option<Body> {
+        self.storage().find_body(name)
+    }
+
+    /// Borrows all the bodies from the storage whose name starts with `prefix`.
+    pub fn find_bodies(&self, prefix: &str) -> Vec<Body> {
+        self.storage().find_bodies(prefix)
+    }
+
     pub fn threads(&self) -> usize {
         unsafe { ffi::NewtonGetThreadsCount(self.as_raw()) as _ }
     }
@@ -194,6 +449,250 @@ impl Newton {
         Bodies { newton: self.as_raw(), next, _phantom: PhantomData }
     }
 
+    /// Calls `callback` once for every body in the world, distributing the work over
+    /// Newton's own worker threads.
+    ///
+    /// The closure receives a reference to the body, which can't escape the call, and
+    /// the index of the worker thread it is running on. The world can't be updated while
+    /// the bodies are being visited.
+    ///
+    /// If the closure panics, the remaining bodies are skipped and the panic is resumed
+    /// on the calling thread once all the worker threads are done.
+    ///
+    /// ```
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    ///
+    /// use newton::prelude::*;
+    /// use newton::{DynamicBody, Newton, Sphere};
+    ///
+    /// let newton = Newton::config().threads(4).build();
+    /// let sphere = Sphere::create(&newton, 0.5, None);
+    /// for i in 0..16 {
+    ///     DynamicBody::builder(&newton, &sphere).position([i as f32, 0.0, 0.0]).into_handle();
+    /// }
+    ///
+    /// let visited = AtomicUsize::new(0);
+    /// newton.par_for_each_body(|_, _| {
+    ///     visited.fetch_add(1, Ordering::Relaxed);
+    /// });
+    /// assert_eq!(16, visited.load(Ordering::Relaxed));
+    /// ```
+    pub fn par_for_each_body<F>(&self, callback: F)
+        where F: Fn(&Body, usize) + Sync
+    {
+        let bodies: Vec<_> = self.bodies_iter().map(|b| b.as_raw()).collect();
+        let job = ParallelJob { bodies: &bodies,
+                                next: AtomicUsize::new(0),
+                                callback: &callback,
+                                panic: Mutex::new(None) };
+
+        unsafe {
+            for _ in 0..self.threads().max(1) {
+                ffi::NewtonDispachThreadJob(self.as_raw(),
+                                            Some(for_each_body::<F>),
+                                            mem::transmute(&job),
+                                            b"par_for_each_body\0".as_ptr() as _);
+            }
+            ffi::NewtonSyncThreadJobs(self.as_raw());
+        }
+
+        if let Some(payload) = job.panic.into_inner().unwrap() {
+            panic::resume_unwind(payload);
+        }
+
+        unsafe extern "C" fn for_each_body<F>(_: *const ffi::NewtonWorld,
+                                              udata: *const c_void,
+                                              thread: c_int)
+            where F: Fn(&Body, usize) + Sync
+        {
+            let job = mem::transmute::<_, &ParallelJob<F>>(udata);
+
+            // each job keeps pulling bodies until the list is exhausted, so busy threads
+            // don't hold back the idle ones.
+            let result = panic::catch_unwind(AssertUnwindSafe(|| loop {
+                let next = job.next.fetch_add(1, Ordering::Relaxed);
+                match job.bodies.get(next) {
+                    Some(&body) => (job.callback)(&Body::from_raw(body, false), thread as _),
+                    None => break,
+                }
+            }));
+
+            // unwinding into Newton is undefined, so the panic is stored and resumed by the
+            // calling thread. Only the first one is kept.
+            if let Err(payload) = result {
+                job.next.store(job.bodies.len(), Ordering::Relaxed);
+                job.panic.lock().unwrap().get_or_insert(payload);
+            }
+        }
+    }
+
+    /// Returns the buffer of deferred world mutations.
+    pub fn commands(&self) -> Commands {
+        unsafe { Commands::from_world(self.as_raw()) }
+    }
+
+    /// Registers a force field, and returns a handle to remove it later.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use newton::prelude::*;
+    /// use newton::handle::ForceFieldHandle;
+    /// use newton::newton::force_field::ForceField;
+    /// use newton::{DynamicBody, Newton, Sphere};
+    ///
+    /// let mut newton = Newton::config().gravity([0.0, -9.8, 0.0]).build();
+    /// let (falling, pushed) = {
+    ///     let sphere = Sphere::create(&newton, 0.5, None);
+    ///     let falling = DynamicBody::builder(&newton, &sphere).mass(1.0).into_handle();
+    ///     let pushed =
+    ///         DynamicBody::builder(&newton, &sphere).position([10.0, 0.0, 0.0]).mass(1.0).into_handle();
+    ///     (falling, pushed)
+    /// };
+    ///
+    /// // only the second body is within the field
+    /// let field = ForceField::directional([5.0, 0.0, 0.0]).region([8.0, -100.0, -2.0], [12.0, 100.0, 2.0]);
+    /// let field = newton.add_force_field(field);
+    ///
+    /// let step = Duration::new(0, 1_000_000_000 / 60);
+    /// for _ in 0..10 {
+    ///     newton.update(step);
+    /// }
+    ///
+    /// let [x, y, _] = newton.storage().body(falling).unwrap().velocity();
+    /// assert!(y < 0.0 && x.abs() < 1e-4);
+    /// let [x, y, _] = newton.storage().body(pushed).unwrap().velocity();
+    /// assert!(y < 0.0 && x > 0.0);
+    ///
+    /// assert!(newton.remove_force_field(field).is_some());
+    /// assert!(newton.remove_force_field(field).is_none());
+    /// assert!(newton.remove_force_field(ForceFieldHandle::null()).is_none());
+    /// ```
+    pub fn add_force_field(&self, field: ForceField) -> ForceFieldHandle {
+        let udata = unsafe { UserData::from_world(self.as_raw()) };
+        let idx = udata.force_fields.write().unwrap().insert(field);
+        ForceFieldHandle::from_index(idx)
+    }
+
+    /// Unregisters a force field.
+    pub fn remove_force_field(&self, handle: ForceFieldHandle) -> Option<ForceField> {
+        let udata = unsafe { UserData::from_world(self.as_raw()) };
+        let mut fields = udata.force_fields.write().unwrap();
+        if fields.contains(handle.index()) {
+            Some(fields.remove(handle.index()))
+        } else {
+            None
+        }
+    }
+
+    /// Enables or disables collisions between two layers (in both directions).
+    ///
+    /// All layers collide with each other by default. Pairs that are filtered out are
+    /// discarded as soon as their AABBs overlap, so they never generate contacts.
+    ///
+    /// The filter is installed as the AABB overlap callback of every material pair formed
+    /// by the default group and the groups created with `create_material_group`. Pairs of
+    /// groups created directly through the Newton API, or whose callback is replaced, are
+    /// not filtered.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use newton::ffi;
+    /// use newton::prelude::*;
+    /// use newton::{DynamicBody, Newton, Sphere};
+    ///
+    /// const PLAYERS: u8 = 1;
+    /// const PROJECTILES: u8 = 2;
+    ///
+    /// // returns the number of contacts and the distance between two overlapping bodies
+    /// fn simulate(collide: bool) -> (i32, f32) {
+    ///     let mut newton = Newton::create();
+    ///     newton.set_layers_collide(PLAYERS, PROJECTILES, collide);
+    ///     assert_eq!(collide, newton.layers_collide(PROJECTILES, PLAYERS));
+    ///
+    ///     let (player, bullet) = {
+    ///         let sphere = Sphere::create(&newton, 0.5, None);
+    ///         let body = |position, layer| {
+    ///             let body = DynamicBody::builder(&newton, &sphere).position(position)
+    ///                                                           .mass(1.0)
+    ///                                                           .build();
+    ///             body.set_layer(layer);
+    ///             body.into_handle(&newton)
+    ///         };
+    ///         (body([0.0, 0.0, 0.0], PLAYERS), body([0.6, 0.0, 0.0], PROJECTILES))
+    ///     };
+    ///
+    ///     for _ in 0..10 {
+    ///         newton.update(Duration::new(0, 1_000_000_000 / 60));
+    ///     }
+    ///
+    ///     let player = newton.storage().body(player).unwrap();
+    ///     let bullet = newton.storage().body(bullet).unwrap();
+    ///     let contacts = unsafe {
+    ///         let mut count = 0;
+    ///         let mut joint = ffi::NewtonBodyGetFirstContactJoint(player.as_raw());
+    ///         while !joint.is_null() {
+    ///             count += ffi::NewtonContactJointGetContactCount(joint);
+    ///             joint = ffi::NewtonBodyGetNextContactJoint(player.as_raw(), joint);
+    ///         }
+    ///         count
+    ///     };
+    ///     (contacts, bullet.position()[0] - player.position()[0])
+    /// }
+    ///
+    /// // filtered out: no contacts, the bodies stay where they are
+    /// let (contacts, distance) = simulate(false);
+    /// assert_eq!(0, contacts);
+    /// assert!((distance - 0.6).abs() < 1e-4);
+    ///
+    /// // colliding: the bodies are pushed apart
+    /// let (contacts, distance) = simulate(true);
+    /// assert!(contacts > 0);
+    /// assert!(distance > 0.6);
+    /// ```
+    ///
+    /// ## Panics
+    /// Panics if a layer is not lower than `layers::LAYERS`.
+    pub fn set_layers_collide(&self, a: u8, b: u8, collide: bool) {
+        let (a, b) = (a as usize, b as usize);
+        assert!(a < LAYERS && b < LAYERS, "Invalid collision layer");
+
+        let udata = unsafe { UserData::from_world(self.as_raw()) };
+        let mut matrix = udata.layer_matrix.write().unwrap();
+        if collide {
+            matrix[a] |= 1 << b;
+            matrix[b] |= 1 << a;
+        } else {
+            matrix[a] &= !(1 << b);
+            matrix[b] &= !(1 << a);
+        }
+    }
+
+    pub fn layers_collide(&self, a: u8, b: u8) -> bool {
+        let (a, b) = (a as usize, b as usize);
+        assert!(a < LAYERS && b < LAYERS, "Invalid collision layer");
+
+        let udata = unsafe { UserData::from_world(self.as_raw()) };
+        udata.layer_matrix.read().unwrap()[a] & (1 << b) != 0
+    }
+
+    /// Creates a new material group, with the collision layer filter installed on all of
+    /// its pairs. Assign it to bodies with `set_material_group_id`.
+    pub fn create_material_group(&self) -> i32 {
+        unsafe {
+            let id = ffi::NewtonMaterialCreateGroupID(self.as_raw());
+            layers::add_group(self.as_raw(), id);
+            id
+        }
+    }
+
+    /// Enters the world critical section. `thread_idx` is the index of the calling thread,
+    /// as given to the callbacks.
+    pub fn critical_section(&self, thread_idx: usize) -> CriticalSection {
+        unsafe { CriticalSection::lock(self.as_raw(), thread_idx) }
+    }
+
     /// Invalidated any cached contacts.
     pub fn invalidate(&mut self) {
         unsafe { ffi::NewtonInvalidateCache(self.as_raw()) }
@@ -203,6 +702,77 @@ impl Newton {
     pub fn update(&mut self, step: Duration) {
         let seconds = step.as_secs() * 1_000_000_000 + step.subsec_nanos() as u64;
         unsafe { ffi::NewtonUpdate(self.as_raw(), seconds as f32 / 1_000_000_000.0) }
+        self.after_update();
+    }
+
+    fn after_update(&mut self) {
+        self.apply_sleep_threshold();
+        self.notify_sleep_states();
+        commands::apply(self);
+    }
+
+    fn notify_sleep_states(&self) {
+        let udata = unsafe { UserData::from_world(self.as_raw()) };
+        // copied, so callbacks can register other bodies
+        let watched: Vec<_> = udata.sleep_watched.lock().unwrap().iter().cloned().collect();
+        for body in watched {
+            unsafe { body::notify_sleep_state(body) };
+        }
+    }
+
+    /// Registers a body whose sleep callback is called after every update.
+    pub(crate) unsafe fn watch_sleep_state(body: *const ffi::NewtonBody) {
+        let udata = UserData::from_world(ffi::NewtonBodyGetWorld(body));
+        udata.sleep_watched.lock().unwrap().insert(body);
+    }
+
+    /// Unregisters a body when it is destroyed.
+    pub(crate) unsafe fn unwatch_sleep_state(body: *const ffi::NewtonBody) {
+        // the world user data is already freed when the world destroys its bodies
+        let udata = ffi::NewtonWorldGetUserData(ffi::NewtonBodyGetWorld(body));
+        if !udata.is_null() {
+            let udata = &*(udata as *const UserData);
+            udata.sleep_watched.lock().unwrap().remove(&body);
+        }
+    }
+
+    /// Sets a callback that is called for every island (group of touching or jointed
+    /// bodies) before it is simulated. Returning `false` skips the island for that step.
+    ///
+    /// The callback may be called from multiple threads at once.
+    ///
+    /// ```
+    /// use newton::Newton;
+    ///
+    /// let newton = Newton::create();
+    ///
+    /// // don't simulate islands with more than 100 bodies
+    /// newton.set_island_callback(|island| island.len() <= 100);
+    /// # newton.clear_island_callback();
+    /// ```
+    pub fn set_island_callback<F>(&self, callback: F)
+        where F: Fn(&Island) -> bool + Send + Sync + 'static
+    {
+        let udata = unsafe { UserData::from_world(self.as_raw()) };
+        *udata.island.write().unwrap() = Some(Box::new(callback));
+        unsafe { ffi::NewtonSetIslandUpdateEvent(self.as_raw(), Some(island_update)) }
+    }
+
+    pub fn clear_island_callback(&self) {
+        let udata = unsafe { UserData::from_world(self.as_raw()) };
+        // the event is still needed by the sleep threshold
+        if udata.sleep_threshold.is_none() {
+            unsafe { ffi::NewtonSetIslandUpdateEvent(self.as_raw(), None) }
+        }
+        *udata.island.write().unwrap() = None;
+    }
+
+    fn apply_sleep_threshold(&self) {
+        let udata = unsafe { UserData::from_world(self.as_raw()) };
+        let resting = mem::replace(&mut *udata.resting.lock().unwrap(), Vec::new());
+        for body in resting {
+            unsafe { ffi::NewtonBodySetSleepState(body, SleepState::Sleeping as _) }
+        }
     }
 
     /// Steps the simulation by a fixed amount (asynchronous) without blocking the
@@ -260,7 +830,11 @@ impl Newton {
                                                    -> u32
             where P: FnMut(Body, Collision) -> bool + Send
         {
-            let b = Body::from_raw(body, false);
+            // bodies of unsupported types are left out of the results
+            let b = match Body::try_from_raw(body, false) {
+                Some(b) => b,
+                None => return 0,
+            };
             let c = Collision::from_raw(col, false);
 
             if mem::transmute::<_, &mut P>(udata)(b, c) {
@@ -306,11 +880,34 @@ impl Newton {
     }
 }
 
+unsafe extern "C" fn island_update(world: *const ffi::NewtonWorld,
+                                   island: *const c_void,
+                                   count: c_int)
+                                   -> c_int {
+    let udata = UserData::from_world(world);
+    let island = Island { raw: island, len: count as _, _phantom: PhantomData };
+
+    let simulate = match &*udata.island.read().unwrap() {
+        Some(callback) => callback(&island),
+        None => true,
+    };
+
+    if let Some((linear, angular)) = udata.sleep_threshold {
+        if simulate && island.is_resting(linear, angular) {
+            let bodies = (0..island.len).map(|i| ffi::NewtonIslandGetBody(island.raw, i as _));
+            udata.resting.lock().unwrap().extend(bodies);
+        }
+    }
+
+    simulate as c_int
+}
+
 impl Drop for Newton {
     fn drop(&mut self) {
         if self.owned {
             unsafe {
                 let udata = ffi::NewtonWorldGetUserData(self.raw);
+                ffi::NewtonWorldSetUserData(self.raw, ptr::null_mut());
                 let _: Box<UserData> = Box::from_raw(udata as _);
                 ffi::NewtonDestroyAllBodies(self.raw);
                 ffi::NewtonMaterialDestroyAllGroupID(self.raw);
@@ -85,7 +85,7 @@ impl<'a> RayCastAlgorithm<'a> for ClosestHit {
                               col_id: Some(col_id), } = udata
         {
             unsafe {
-                Some(RayHit { body: Body::from_raw(body, false),
+                Some(RayHit { body: Body::try_from_raw(body, false)?,
                               collision: Collision::from_raw(col, false),
                               collision_id: col_id,
                               position: contact,
@@ -106,6 +106,11 @@ impl<'a> RayCastAlgorithm<'a> for ClosestHit {
                                      -> f32 {
             let mut udata = mem::transmute::<_, &mut Udata>(user_data);
 
+            // bodies of unsupported types are skipped, and the ray goes on
+            if Body::try_from_raw(body, false).is_none() {
+                return udata.param.unwrap_or(1.0);
+            }
+
             if intersect_param < udata.param.unwrap_or(2.0) {
                 udata.contact = Some(mem::transmute::<_, &Vec3>(contact).clone());
                 udata.normal = Some(mem::transmute::<_, &Vec3>(normal).clone());
@@ -175,12 +180,14 @@ impl<'a> RayCastAlgorithm<'a> for NClosestHits {
 
         return udata.heap
                     .iter()
-                    .map(|n| RayHit { body: unsafe { Body::from_raw(n.body, false) },
+                    .filter_map(|n| {
+                        Some(RayHit { body: unsafe { Body::try_from_raw(n.body, false)? },
                                       collision: unsafe { Collision::from_raw(n.collision, false) },
                                       position: n.contact,
                                       normal: n.normal,
                                       collision_id: n.collision_id,
                                       intersect_param: n.intersect })
+                    })
                     .collect();
 
         unsafe extern "C" fn cfilter(body: *const ffi::NewtonBody,
@@ -193,6 +200,10 @@ impl<'a> RayCastAlgorithm<'a> for NClosestHits {
                                      -> f32 {
             let mut udata = mem::transmute::<_, &mut Udata>(user_data);
 
+            if Body::try_from_raw(body, false).is_none() {
+                return udata.heap.peek().map(|h| h.intersect).unwrap_or(1.0);
+            }
+
             udata.heap.push(Node { intersect,
                                    body,
                                    collision,
@@ -1,38 +1,92 @@
-use std::collections::{BTreeSet, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::ops::Bound;
 use std::sync::RwLock;
 
 use crate::body::{Body, NewtonBody};
 use crate::collision::{Collision, NewtonCollision};
-use crate::handle::{Handle, HandleInner};
-use crate::joint::Joint;
+use crate::handle::{BodyHandle, CollisionHandle, HandleInner, JointHandle};
+use crate::joint::{Joint, NewtonJoint};
 
-/// Data structure for Newton Bodies & Collisions.
+/// Data structure for Newton Bodies, Collisions & Joints.
 pub trait NewtonStorage {
     /// Stores the given Newton Body.
-    fn move_body(&self, body: Body) -> Handle;
+    fn move_body(&self, body: Body) -> BodyHandle;
 
     /// Stores the given collision.
-    fn move_collision(&self, col: Collision) -> Handle;
+    fn move_collision(&self, col: Collision) -> CollisionHandle;
 
-    /// Stores the given constraint
-    fn move_constraint(&self, con: Joint) -> Handle;
+    /// Stores the given Newton Joint.
+    fn move_constraint(&self, con: Joint) -> JointHandle;
 
     /// Borrows a Newton Body.
-    fn body(&self, handle: Handle) -> Option<Body>;
+    fn body(&self, handle: BodyHandle) -> Option<Body>;
 
     /// Borrows a Newton Collision.
-    fn collision(&self, handle: Handle) -> Option<Collision>;
+    fn collision(&self, handle: CollisionHandle) -> Option<Collision>;
 
-    /// Borrows constraint
-    fn constraint(&self, handle: Handle) -> Option<Joint>;
+    /// Borrows a Newton Joint.
+    ///
+    /// Joints are destroyed by Newton along with any of the bodies they connect, and
+    /// their handles are removed from the storage when that happens.
+    ///
+    /// ```
+    /// use newton::prelude::*;
+    /// use newton::{Ball, DynamicBody, Newton, Sphere};
+    ///
+    /// let mut newton = Newton::create();
+    /// let identity = [[1.0, 0.0, 0.0, 0.0],
+    ///                 [0.0, 1.0, 0.0, 0.0],
+    ///                 [0.0, 0.0, 1.0, 0.0],
+    ///                 [0.0, 0.0, 0.0, 1.0]];
+    ///
+    /// let (parent, first, second) = {
+    ///     let sphere = Sphere::create(&newton, 0.5, None);
+    ///     let child = DynamicBody::create(&newton, &sphere, identity, Some("child"));
+    ///     let parent = DynamicBody::create(&newton, &sphere, identity, Some("parent"));
+    ///
+    ///     let first = Ball::create(&newton, [0.0, 0.0, 0.0], &child, Some(&parent), Some("first"));
+    ///     let second = Ball::create(&newton, [0.0, 1.0, 0.0], &child, Some(&parent), Some("second"));
+    ///     let (first, second) = (first.into_handle(&newton), second.into_handle(&newton));
+    ///
+    ///     child.into_handle(&newton);
+    ///     (parent.into_handle(&newton), first, second)
+    /// };
+    ///
+    /// let storage = newton.storage_mut();
+    /// assert!(storage.constraint(first).is_some());
+    ///
+    /// // dropping the joint destroys it
+    /// assert!(storage.take_constraint(first).is_some());
+    /// assert!(storage.constraint(first).is_none());
+    ///
+    /// // destroying a connected body destroys the joint too
+    /// drop(storage.take_body(parent));
+    /// assert!(storage.constraint(second).is_none());
+    /// assert!(storage.take_constraint(second).is_none());
+    /// ```
+    fn constraint(&self, handle: JointHandle) -> Option<Joint>;
 
     /// Retakes ownership of a Newton Body.
-    fn take_body(&mut self, handle: Handle) -> Option<Body>;
+    fn take_body(&mut self, handle: BodyHandle) -> Option<Body>;
 
     /// Retakes ownership of a Newton Collision.
-    fn take_collision(&mut self, handle: Handle) -> Option<Collision>;
+    fn take_collision(&mut self, handle: CollisionHandle) -> Option<Collision>;
 
-    fn take_constraint(&mut self, handle: Handle) -> Option<Collision>;
+    /// Retakes ownership of a Newton Joint.
+    fn take_constraint(&mut self, handle: JointHandle) -> Option<Joint>;
+
+    /// Removes the handle of a joint that Newton has destroyed, without destroying it again.
+    fn forget_constraint(&self, handle: JointHandle);
+
+    /// Borrows a Newton Body by name.
+    fn find_body(&self, name: &str) -> Option<Body>;
+
+    /// Borrows all the Newton Bodies whose name starts with the given prefix.
+    fn find_bodies(&self, prefix: &str) -> Vec<Body>;
+
+    /// Updates the name index after a body has been renamed.
+    /// Bodies that are not in the storage are ignored.
+    fn rename_body(&self, handle: BodyHandle, old: Option<&str>, new: Option<&str>);
 }
 
 macro_rules! set {
@@ -41,8 +95,33 @@ macro_rules! set {
             #[derive(Debug, Default)]
             $(#[$($meta)+])*
             pub struct $name {
-                bodies: RwLock<$data_struct<Handle>>,
-                collisions: RwLock<$data_struct<Handle>>,
+                bodies: RwLock<$data_struct<BodyHandle>>,
+                collisions: RwLock<$data_struct<CollisionHandle>>,
+                joints: RwLock<$data_struct<JointHandle>>,
+
+                // Body name index. Names are not unique.
+                names: RwLock<BTreeMap<String, Vec<BodyHandle>>>,
+            }
+
+            impl $name {
+                fn index_name(&self, handle: BodyHandle, name: &str) {
+                    let mut names = self.names.write().unwrap();
+                    names.entry(name.to_string()).or_insert_with(Vec::new).push(handle);
+                }
+
+                fn unindex_name(&self, handle: BodyHandle, name: &str) {
+                    let mut names = self.names.write().unwrap();
+                    let empty = match names.get_mut(name) {
+                        Some(handles) => {
+                            handles.retain(|h| *h != handle);
+                            handles.is_empty()
+                        }
+                        None => false,
+                    };
+                    if empty {
+                        names.remove(name);
+                    }
+                }
             }
 
             impl Drop for $name {
@@ -59,23 +138,28 @@ macro_rules! set {
             }
 
             impl NewtonStorage for $name {
-                fn move_body(&self, body: Body) -> Handle {
-                    let handle = Handle::from_ptr(body.as_raw() as _);
+                fn move_body(&self, body: Body) -> BodyHandle {
+                    let handle = BodyHandle::from_ptr(body.as_raw() as _);
+                    if let Some(name) = body.name() {
+                        self.index_name(handle, &name);
+                    }
                     self.bodies.write().unwrap().insert(handle.clone());
                     handle
                 }
 
-                fn move_collision(&self, col: Collision) -> Handle {
-                    let handle = Handle::from_ptr(col.as_raw() as _);
+                fn move_collision(&self, col: Collision) -> CollisionHandle {
+                    let handle = CollisionHandle::from_ptr(col.as_raw() as _);
                     self.collisions.write().unwrap().insert(handle.clone());
                     handle
                 }
 
-                fn move_constraint(&self, con: Joint) -> Handle {
-                    unimplemented!()
+                fn move_constraint(&self, con: Joint) -> JointHandle {
+                    let handle = JointHandle::from_ptr(con.as_raw() as _);
+                    self.joints.write().unwrap().insert(handle.clone());
+                    handle
                 }
 
-                fn body(&self, handle: Handle) -> Option<Body> {
+                fn body(&self, handle: BodyHandle) -> Option<Body> {
                     let body = self.bodies.read().unwrap().get(&handle).cloned();
                     unsafe {
                         body.map(|h| match h.inner() {
@@ -85,7 +169,7 @@ macro_rules! set {
                     }
                 }
 
-                fn collision(&self, handle: Handle) -> Option<Collision> {
+                fn collision(&self, handle: CollisionHandle) -> Option<Collision> {
                     let collision = self.collisions.read().unwrap().get(&handle).cloned();
                     unsafe {
                         collision.map(|h| match h.inner() {
@@ -95,21 +179,32 @@ macro_rules! set {
                     }
                 }
 
-                fn constraint(&self, handle: Handle) -> Option<Joint> {
-                    unimplemented!()
+                fn constraint(&self, handle: JointHandle) -> Option<Joint> {
+                    let joint = self.joints.read().unwrap().get(&handle).cloned();
+                    unsafe {
+                        // only pointer handles are stored
+                        joint.and_then(|h| match h.inner() {
+                            HandleInner::Pointer(ptr) => Some(Joint::from_raw(ptr as _, false)),
+                            HandleInner::Index(_) => None,
+                        })
+                    }
                 }
 
-                fn take_body(&mut self, handle: Handle) -> Option<Body> {
+                fn take_body(&mut self, handle: BodyHandle) -> Option<Body> {
                     let body = self.bodies.write().unwrap().take(&handle);
-                    unsafe {
+                    let body = unsafe {
                         body.map(|h| match h.inner() {
                             HandleInner::Pointer(ptr) => Body::from_raw(ptr as _, true),
                             _ => unimplemented!("index indexing"),
                         })
+                    };
+                    if let Some(name) = body.as_ref().and_then(|b| b.name()) {
+                        self.unindex_name(handle, &name);
                     }
+                    body
                 }
 
-                fn take_collision(&mut self, handle: Handle) -> Option<Collision> {
+                fn take_collision(&mut self, handle: CollisionHandle) -> Option<Collision> {
                     let collision = self.collisions.write().unwrap().take(&handle);
                     unsafe {
                         collision.map(|h| match h.inner() {
@@ -119,8 +214,46 @@ macro_rules! set {
                     }
                 }
 
-                fn take_constraint(&mut self, handle: Handle) -> Option<Collision> {
-                    unimplemented!()
+                fn take_constraint(&mut self, handle: JointHandle) -> Option<Joint> {
+                    let joint = self.joints.write().unwrap().take(&handle);
+                    unsafe {
+                        // only pointer handles are stored
+                        joint.and_then(|h| match h.inner() {
+                            HandleInner::Pointer(ptr) => Some(Joint::from_raw(ptr as _, true)),
+                            HandleInner::Index(_) => None,
+                        })
+                    }
+                }
+
+                fn forget_constraint(&self, handle: JointHandle) {
+                    self.joints.write().unwrap().remove(&handle);
+                }
+
+                fn find_body(&self, name: &str) -> Option<Body> {
+                    let names = self.names.read().unwrap();
+                    let handle = names.get(name).and_then(|handles| handles.first()).cloned();
+                    handle.and_then(|h| self.body(h))
+                }
+
+                fn find_bodies(&self, prefix: &str) -> Vec<Body> {
+                    let names = self.names.read().unwrap();
+                    names.range::<str, _>((Bound::Included(prefix), Bound::Unbounded))
+                         .take_while(|(name, _)| name.starts_with(prefix))
+                         .flat_map(|(_, handles)| handles.iter())
+                         .filter_map(|h| self.body(*h))
+                         .collect()
+                }
+
+                fn rename_body(&self, handle: BodyHandle, old: Option<&str>, new: Option<&str>) {
+                    if !self.bodies.read().unwrap().contains(&handle) {
+                        return;
+                    }
+                    if let Some(old) = old {
+                        self.unindex_name(handle, old);
+                    }
+                    if let Some(new) = new {
+                        self.index_name(handle, new);
+                    }
                 }
             }
 
@@ -129,9 +262,9 @@ macro_rules! set {
 }
 
 set! {
-    /// Storage of bodies & collisions in a HashSet.
+    /// Storage of bodies, collisions & joints in a HashSet.
     struct HashStorage<HashSet>
 
-    /// Storage of bodies & collisions in a BTree.
+    /// Storage of bodies, collisions & joints in a BTree.
     struct BTreeStorage<BTreeSet>
 }
@@ -23,7 +23,8 @@ use crate::math::*;
 use crate::prelude::*;
 
 use crate::body::Body;
-use crate::handle::Handle;
+use crate::collision::Params;
+use crate::handle::BodyHandle;
 use crate::newton::ray_cast::ClosestHit;
 use crate::newton::Newton;
 
@@ -76,9 +77,11 @@ impl Stats {
 #[derive(ImGuiExt, Clone)]
 pub struct SelectedBody {
     #[imgui(display(display = "{:?}", 0))]
-    body: (Handle,),
+    body: (BodyHandle,),
     #[imgui(display(display = "{:?}", 0))]
-    name: (Option<&'static str>,),
+    name: (Option<String>,),
+    #[imgui(display(display = "{}", 0))]
+    collision: (String,),
     #[imgui(new_line, checkbox)]
     awake: bool,
     #[imgui(drag(speed = 0.1))]
@@ -99,15 +102,16 @@ const fn disabled_text() -> ImGuiInputTextFlags {
 }
 
 impl SelectedBody {
-    fn from_handle(handle: Handle) -> Self {
+    fn from_handle(handle: BodyHandle) -> Self {
         Self { body: (handle,), ..Default::default() }
     }
 }
 
 impl Default for SelectedBody {
     fn default() -> Self {
-        Self { body: (Handle::null(),),
+        Self { body: (BodyHandle::null(),),
                name: (None,),
+               collision: (String::new(),),
                awake: true,
                position: Default::default(),
                velocity: Default::default(),
@@ -554,8 +558,12 @@ impl<T: Testbed + Send + Sync> Runner<T> {
                 let body = self.newton.storage().body(ptr).unwrap();
                 sel.position = body.position();
                 sel.velocity = body.velocity();
-                sel.name = (body.name(),);
-                //sel.collision = (body.collision().collision_type(),);
+                sel.name = (body.name().map(String::from),);
+                sel.collision = (match body.collision().params() {
+                    Params::HeightFieldF32(p) => format!("HeightFieldF32 {}x{}", p.width, p.height),
+                    Params::HeightFieldU16(p) => format!("HeightFieldU16 {}x{}", p.width, p.height),
+                    params => format!("{:?}", params),
+                },);
 
                 match &body {
                     Body::Dynamic(ref body) => {